@@ -6,5 +6,5 @@ fn main() {
 
     let request = FudgeSession::build(dice_request);
 
-    println!("{}", request.to_string());
+    println!("{}", request);
 }
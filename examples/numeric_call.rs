@@ -8,5 +8,5 @@ fn main() {
     ];
 
     let request = NumericSession::build(dice_request);
-    println!("{}", request.to_string());
+    println!("{}", request);
 }
@@ -6,7 +6,7 @@ fn main() {
     match request {
         Err(msg) => eprintln!("FAILURE : {}", msg),
         Ok(ref mut req) => {
-            println!("{}", req.to_string());
+            println!("{}", req);
         }
     }
 }
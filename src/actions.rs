@@ -7,18 +7,21 @@
 use crate::dice::NumericRolls;
 use crate::dice::*;
 use crate::errors::Error;
+use crate::io::write::describe_with_audit;
 use crate::NumericSession;
+use crate::SymbolSession;
 use crate::TypedRollSession;
 use std::collections::HashMap;
 use std::fmt;
 
 /// Enumeration of all possible actions
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub enum Action {
-    /// Rerolls the dice for the values equal to the action parameters (numeric rolls only, cf. trait [Reroll](trait.Reroll.html)).
-    RerollNumeric(Vec<NumericRoll>),
-    /// Rerolls the dice for the values equal to the action parameters (fudge rolls only, cf. trait [Reroll](trait.Reroll.html)).
-    RerollFudge(Vec<FudgeRoll>),
+    /// Rerolls the dice for the values matching the action condition, once or until they don't match (numeric rolls only, cf. trait [Reroll](trait.Reroll.html)).
+    RerollNumeric(RollCondition, RerollMode),
+    /// Rerolls the dice for the values equal to the action parameters, once or until they aren't (fudge rolls only, cf. trait [Reroll](trait.Reroll.html)).
+    RerollFudge(Vec<FudgeRoll>, RerollMode),
     /// Sum the rolls for each dice (numeric rolls only, cf. trait [Sum](trait.Sum.html)).
     Sum,
     // Sum all the dice (numeric rolls only, cf. trait [TotalSum](trait.TotalSum.html)).
@@ -29,8 +32,12 @@ pub enum Action {
     MultiplyBy(NumericRoll),
     /// Invert the digits of the rolls (numeric rolls only, cf. trait [FlipFlop](trait.FlipFlop.html)).   
     FlipFlop,
-    /// Add new rolls for rolls equal to the action parameters (numeric rolls only, cf. trait [Explode](trait.Explode.html)).   
-    Explode(Vec<NumericRoll>),
+    /// Add new rolls for rolls matching the action condition (numeric rolls only, cf. trait [Explode](trait.Explode.html)).   
+    Explode(RollCondition),
+    /// Add new rolls to the rolls matching the action condition, instead of new dice (numeric rolls only, cf. trait [Compound](trait.Compound.html)).
+    Compound(RollCondition),
+    /// Add new rolls minus one for rolls matching the action condition (numeric rolls only, cf. trait [Penetrate](trait.Penetrate.html)).
+    Penetrate(RollCondition),
     /// Add new rolls for rolls equal to the action parameters (fudge rolls only, cf. trait [Explode](trait.Explode.html)).   
     ExplodeFudge(Vec<FudgeRoll>),
     /// Keep only the N best rolls (numeric rolls only, cf. trait [KeepBest](trait.KeepBest.html)).   
@@ -41,6 +48,24 @@ pub enum Action {
     RerollBest(DiceNumber),
     /// Reroll the N worst rolls (numeric rolls only, cf. trait [RerollWorst](trait.RerollWorst.html)).   
     RerollWorst(DiceNumber),
+    /// Negate the rolls, so that they are subtracted from the total (cf. trait [Negate](trait.Negate.html)).
+    Negate,
+    /// Count the rolls successfully compared to a target, minus the rolls equal to the botch values (numeric rolls only, cf. trait [CountSuccesses](trait.CountSuccesses.html)).
+    CountSuccesses(Comparison, NumericRoll, Vec<NumericRoll>),
+    /// Roll the dice group a second time and keep the rolls with the best total (numeric rolls only, cf. trait [Advantage](trait.Advantage.html)).
+    Advantage,
+    /// Roll the dice group a second time and keep the rolls with the worst total (numeric rolls only, cf. trait [Advantage](trait.Advantage.html)).
+    Disadvantage,
+    /// Flag the natural rolls matching the condition (the highest face by default) as critical successes, and the lowest face as critical failures (numeric rolls only, cf. trait [DetectCritical](trait.DetectCritical.html)).
+    Critical(Option<RollCondition>),
+    /// Double the number of dice of the group when another group of the request rolls a critical success (numeric rolls only, the constant modifiers are not doubled).
+    CriticalDamage,
+    /// Roll N more tens dice for each roll and keep the lowest result (percentile rolls only, cf. trait [BonusDice](trait.BonusDice.html)).
+    Bonus(DiceNumber),
+    /// Roll N more tens dice for each roll and keep the highest result (percentile rolls only, cf. trait [BonusDice](trait.BonusDice.html)).
+    Penalty(DiceNumber),
+    /// Replace the rolls by their success level against the action parameter, the skill value (numeric rolls only, cf. trait [SkillCheck](trait.SkillCheck.html)).
+    SkillCheck(NumericRoll),
 }
 impl fmt::Display for Action {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -48,13 +73,89 @@ impl fmt::Display for Action {
     }
 }
 
+impl Action {
+    /// `true` if the action needs a second roll of the whole dice group (cf. [Apply::apply_twice](trait.Apply.html#tymethod.apply_twice)).
+    pub fn rolls_group_twice(&self) -> bool {
+        matches!(self, Action::Advantage | Action::Disadvantage)
+    }
+}
+
+/// Comparison of a roll with a target value
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub enum Comparison {
+    GreaterOrEqual,
+    LowerOrEqual,
+    Equal,
+    Greater,
+    Lower,
+}
+
+impl Comparison {
+    /// Return `true` if the roll is successfully compared to the target.
+    pub fn compare(self, roll: NumericRoll, target: NumericRoll) -> bool {
+        match self {
+            Comparison::GreaterOrEqual => roll >= target,
+            Comparison::LowerOrEqual => roll <= target,
+            Comparison::Equal => roll == target,
+            Comparison::Greater => roll > target,
+            Comparison::Lower => roll < target,
+        }
+    }
+}
+
+/// Rolls that trigger an action: a list of values, a comparison with a threshold (like `>=9`),
+/// or a range of values (like `1..3`, bounds included).
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub enum RollCondition {
+    Values(Vec<NumericRoll>),
+    Threshold(Comparison, NumericRoll),
+    Range(NumericRoll, NumericRoll),
+}
+
+impl RollCondition {
+    /// Return `true` if the roll triggers the action.
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::actions::{Comparison, RollCondition};
+    /// assert!(RollCondition::Values(vec![1, 2]).matches(2));
+    /// assert!(RollCondition::Threshold(Comparison::GreaterOrEqual, 9).matches(10));
+    /// assert!(!RollCondition::Threshold(Comparison::Lower, 3).matches(3));
+    /// assert!(RollCondition::Range(1, 3).matches(3));
+    /// ```
+    pub fn matches(&self, roll: NumericRoll) -> bool {
+        match self {
+            RollCondition::Values(values) => values.contains(&roll),
+            RollCondition::Threshold(comparison, target) => comparison.compare(roll, *target),
+            RollCondition::Range(low, high) => *low <= roll && roll <= *high,
+        }
+    }
+}
+
+/// How many times a die matching a reroll condition is rerolled.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub enum RerollMode {
+    /// Reroll once, and keep the new roll even if it matches again
+    Once,
+    /// Reroll until the roll doesn't match anymore
+    Until,
+}
+
 /// Enumeration of all possible aggregation traits.
 ///
 /// An aggregation is an final action: you can't apply any other action afterward.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub enum Aggregation {
     /// Count occurences of the different result values (cf. trait [CountValues](trait.CountValues.html)).)
     CountValues,
+    /// Sum the rolls sharing the same dice ID, then the grand total (numeric rolls only, cf. trait [SumById](trait.SumById.html)).
+    SumById,
+    /// Count the symbols, each one cancelling one of its opposite symbol (symbol rolls only, cf. trait [CancelSymbols](trait.CancelSymbols.html)).
+    CancelSymbols,
 }
 impl fmt::Display for Aggregation {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -82,12 +183,14 @@ impl MultiplyBy<NumericRolls> for NumericRolls {
         Rolls {
             description: format!("({}) x {}", &self.description, factor),
             dice: self.dice.clone(),
+            id: self.id.clone(),
             rolls: self.rolls.multiply(factor),
+            history: self.history.clone(),
         }
     }
 }
 
-/// Replace the rolls equal to the given value by a new roll
+/// Replace the rolls equal to the given value by a new roll, once or until the new roll is not one of the values
 /// # Example
 /// ```
 /// # use letsroll::actions::Reroll;
@@ -95,38 +198,113 @@ impl MultiplyBy<NumericRolls> for NumericRolls {
 /// let input_rolls = vec![5,1,10];
 /// let dice = DiceGenerator::new();
 /// let dice_request = NumericRollRequest::new(3, NumericDice::RepeatingDice(input_rolls));
-/// let rolls = NumericRolls::new(dice_request, &dice);
-/// assert_eq!(rolls.reroll(&dice, &vec![1]).rolls, vec![5,5,10]);
+/// let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+/// assert_eq!(rolls.reroll(&dice, &vec![1]).unwrap().rolls, vec![5,5,10]);
+/// // The 5 and 1 are rerolled into a 5, then rerolled again
+/// assert!(rolls.reroll_until(&dice, &vec![1, 5]).is_err());
 /// ```
+/// # Errors
+/// Fails if more dice than the [Limits](../dice/struct.Limits.html) `max_rerolls` must be rerolled.
 pub trait Reroll<T: RollBounds, V: DiceBounds> {
-    fn reroll(&self, dice: &Roll<T, V>, t: &Vec<T>) -> Rolls<T, V>;
+    fn reroll(&self, dice: &dyn Roll<T, V>, t: &[T]) -> Result<Rolls<T, V>, Error>;
+
+    fn reroll_until(&self, dice: &dyn Roll<T, V>, t: &[T]) -> Result<Rolls<T, V>, Error>;
 }
 impl<T: RollBounds, V: DiceBounds> Reroll<T, V> for Rolls<T, V> {
     // TODO should the new roll be suject to the same action ?
-    fn reroll(&self, dice: &Roll<T, V>, t: &Vec<T>) -> Rolls<T, V> {
+    fn reroll(&self, dice: &dyn Roll<T, V>, t: &[T]) -> Result<Rolls<T, V>, Error> {
+        self.reroll_when(
+            dice,
+            |roll| t.contains(&roll),
+            RerollMode::Once,
+            reroll_description(&self.description, RerollMode::Once, values_to_string(t)),
+        )
+    }
+
+    fn reroll_until(&self, dice: &dyn Roll<T, V>, t: &[T]) -> Result<Rolls<T, V>, Error> {
+        self.reroll_when(
+            dice,
+            |roll| t.contains(&roll),
+            RerollMode::Until,
+            reroll_description(&self.description, RerollMode::Until, values_to_string(t)),
+        )
+    }
+}
+
+impl<T: RollBounds, V: DiceBounds> Rolls<T, V> {
+    /// Replace each roll that `rerolls` by a new roll, once or until the new roll doesn't `reroll`.
+    fn reroll_when<R: Fn(T) -> bool>(
+        &self,
+        dice: &dyn Roll<T, V>,
+        rerolls: R,
+        mode: RerollMode,
+        description: String,
+    ) -> Result<Rolls<T, V>, Error> {
+        let max_rerolls = dice.limits().max_rerolls;
+        let mut rerolled = 0;
         let mut new_rolls: Vec<T> = vec![];
+        let mut history = self.history.clone();
+        let mut picked: Vec<usize> = vec![];
         for roll in self.rolls.iter() {
-            if t.contains(roll) {
-                new_rolls.append(&mut dice.roll(1, &self.dice));
-            } else {
-                new_rolls.push(*roll);
+            let mut new_roll = *roll;
+            if rerolls(*roll) {
+                let die = self.find_die(*roll, &picked);
+                picked.extend(die);
+                loop {
+                    rerolled += 1;
+                    if rerolled > max_rerolls {
+                        return Err(Error::limit_exceeded("dice rerolled", max_rerolls));
+                    }
+                    new_roll = dice.roll(1, &self.dice)?[0];
+                    if let Some(index) = die {
+                        history[index].values.push(new_roll);
+                    }
+                    if mode == RerollMode::Once || !rerolls(new_roll) {
+                        break;
+                    }
+                }
             }
+            new_rolls.push(new_roll);
         }
-        Rolls {
-            description: format!(
-                "{} Reroll({})",
-                self.description,
-                t.iter()
-                    .map(|val| val.to_string())
-                    .collect::<Vec<String>>()
-                    .join(",")
-            ),
+        Ok(Rolls {
+            description,
             dice: self.dice.clone(),
+            id: self.id.clone(),
             rolls: new_rolls,
-        }
+            history,
+        })
+    }
+}
+
+fn reroll_description<C: fmt::Display>(
+    description: &str,
+    mode: RerollMode,
+    condition: C,
+) -> String {
+    match mode {
+        RerollMode::Once => format!("{} Reroll({})", description, condition),
+        RerollMode::Until => format!("{} RerollUntil({})", description, condition),
     }
 }
 
+/// Sum the rolls, failing instead of overflowing
+pub(crate) fn checked_sum<'a, I: IntoIterator<Item = &'a NumericRoll>>(
+    rolls: I,
+) -> Result<NumericRoll, Error> {
+    rolls
+        .into_iter()
+        .try_fold(0 as NumericRoll, |total, roll| total.checked_add(*roll))
+        .ok_or_else(|| Error::bad_action_parameter("Arithmetic overflow"))
+}
+
+fn values_to_string<T: fmt::Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|val| val.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
 /// Flip the digits of a numbered dice roll.
 /// # Example
 /// Let's simulate a D20 flipflop:
@@ -135,8 +313,8 @@ impl<T: RollBounds, V: DiceBounds> Reroll<T, V> for Rolls<T, V> {
 /// # use letsroll::dice::{DiceGenerator, NumericRolls, NumericDice, NumericRollRequest};
 /// let input_rolls = vec![1,15,20];
 /// let dice_request = NumericRollRequest::new(3, NumericDice::RepeatingDice(input_rolls));
-/// let rolls = NumericRolls::new(dice_request, &DiceGenerator::new());
-/// assert_eq!(rolls.flip().rolls, vec![10,51,2]);
+/// let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
+/// assert_eq!(rolls.flip().unwrap().rolls, vec![10,51,2]);
 /// ```
 /// And now a D100 flipflop:
 /// ```
@@ -144,32 +322,55 @@ impl<T: RollBounds, V: DiceBounds> Reroll<T, V> for Rolls<T, V> {
 /// # use letsroll::dice::{DiceGenerator, NumericRolls, NumericDice, NumericRollRequest};
 /// let input_rolls = vec![1,15,100];
 /// let dice_request = NumericRollRequest::new(3, NumericDice::RepeatingDice(input_rolls));
-/// let rolls = NumericRolls::new(dice_request, &DiceGenerator::new());
-/// assert_eq!(rolls.flip().rolls, vec![100,510,1]);
+/// let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
+/// assert_eq!(rolls.flip().unwrap().rolls, vec![100,510,1]);
+/// ```
+/// The sign of negative rolls is kept:
+/// ```
+/// # use letsroll::actions::FlipFlop;
+/// # use letsroll::dice::{DiceGenerator, NumericRolls, NumericDice, NumericRollRequest};
+/// let input_rolls = vec![-1,-15,20];
+/// let dice_request = NumericRollRequest::new(3, NumericDice::RepeatingDice(input_rolls));
+/// let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
+/// assert_eq!(rolls.flip().unwrap().rolls, vec![-10,-51,2]);
 /// ```
 pub trait FlipFlop<T> {
-    fn flip(&self) -> T;
+    fn flip(&self) -> Result<T, Error>;
 }
 impl FlipFlop<NumericRolls> for NumericRolls {
-    fn flip(&self) -> NumericRolls {
-        Rolls {
+    fn flip(&self) -> Result<NumericRolls, Error> {
+        if self.dice == NumericDice::AggregationResult {
+            return Err(Error::incompatible(
+                &Action::FlipFlop.to_string(),
+                "aggregated rolls",
+            ));
+        }
+        // Compute the max padding required for 1 to become 10, 100, etc. according to the dice sides
+        let max_digits = get_digits_number(self.dice.get_max_value().unsigned_abs() as f32);
+        let rolls = self
+            .rolls
+            .iter()
+            .map(|roll| {
+                let magnitude = roll
+                    .checked_abs()
+                    .ok_or_else(|| Error::bad_action_parameter("Arithmetic overflow"))?;
+                let result = format!("{:0width$}", magnitude, width = max_digits)
+                    .chars()
+                    .rev()
+                    .collect::<String>();
+                let result: NumericRoll = result.parse().map_err(|_err| {
+                    Error::bad_action_parameter("Could not parse result of flip")
+                })?;
+                Ok(result * roll.signum())
+            })
+            .collect::<Result<Vec<NumericRoll>, Error>>()?;
+        Ok(Rolls {
             description: format!("flip({})", &self.description),
             dice: self.dice.clone(),
-            rolls: self
-                .rolls
-                .iter()
-                .map(|roll| {
-                    // Compute the max padding required for 1 to become 10, 100, etc. according to the dice sides
-                    let max_digits = get_digits_number(self.dice.get_max_value() as f32);
-                    let result = format!("{:0width$}", roll, width = max_digits)
-                        .chars()
-                        .rev()
-                        .collect::<String>();
-                    let result: NumericRoll = result.parse().unwrap();
-                    result
-                })
-                .collect(),
-        }
+            id: self.id.clone(),
+            rolls,
+            history: self.history.clone(),
+        })
     }
 }
 fn get_digits_number(n: f32) -> usize {
@@ -183,7 +384,7 @@ fn get_digits_number(n: f32) -> usize {
 /// # use letsroll::actions::Sum;
 /// # use letsroll::dice::{DiceGenerator, NumericRolls, NumericDice, NumericRollRequest};
 /// let dice_request = NumericRollRequest::new(3, NumericDice::ConstDice(10));
-/// let rolls = NumericRolls::new(dice_request, &DiceGenerator::new());
+/// let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
 /// assert_eq!(rolls.sum().rolls, vec![30]);
 /// ```
 /// # Remark
@@ -201,7 +402,9 @@ impl Sum<NumericRolls> for NumericRolls {
         Rolls {
             description: format!("sum({})", &self.description),
             dice: self.dice.clone(),
+            id: self.id.clone(),
             rolls: self.rolls.sum(),
+            history: self.history.clone(),
         }
     }
 }
@@ -217,7 +420,7 @@ impl Sum<NumericRolls> for NumericRolls {
 ///     NumericDice::RepeatingDice(vec![1, 2, 3, 4, 5]),
 /// );
 /// let dice = DiceGenerator::new();
-/// let rolls = NumericRolls::new(dice_request, &dice);
+/// let rolls = NumericRolls::new(dice_request, &dice).unwrap();
 /// let expected = vec![12345];
 /// assert_eq!(rolls.concat().unwrap().rolls, expected);
 /// ```
@@ -226,6 +429,12 @@ pub trait Concat<T> {
 }
 impl Concat<Vec<NumericRoll>> for Vec<NumericRoll> {
     fn concat(&self) -> Result<Vec<NumericRoll>, Error> {
+        if self.iter().any(|roll| *roll < 0) {
+            return Err(Error::incompatible(
+                &Action::Concat.to_string(),
+                "negative numeric roll",
+            ));
+        }
         Ok(vec![self
             .iter()
             .map(|roll| roll.to_string())
@@ -253,11 +462,59 @@ impl Concat<NumericRolls> for NumericRolls {
                     .join(" ")
             ),
             dice: NumericDice::AggregationResult,
+            id: self.id.clone(),
             rolls: self.rolls.concat()?,
+            history: self.history.clone(),
         })
     }
 }
 
+/// Negate the rolls, so that they are subtracted from the total.
+/// A negated fudge roll swaps its sign.
+///
+/// # Example
+/// ```
+/// # use letsroll::actions::Negate;
+/// # use letsroll::dice::FudgeRoll;
+/// let input_rolls = vec![1,-2,3];
+/// assert_eq!(input_rolls.negate(), vec![-1,2,-3]);
+/// let input_rolls = vec![FudgeRoll::Plus, FudgeRoll::Blank, FudgeRoll::Minus];
+/// assert_eq!(input_rolls.negate(), vec![FudgeRoll::Minus, FudgeRoll::Blank, FudgeRoll::Plus]);
+/// ```
+pub trait Negate<T> {
+    fn negate(&self) -> T;
+}
+impl Negate<Vec<NumericRoll>> for Vec<NumericRoll> {
+    fn negate(&self) -> Vec<NumericRoll> {
+        self.iter().map(|roll| -roll).collect()
+    }
+}
+impl Negate<Vec<FudgeRoll>> for Vec<FudgeRoll> {
+    fn negate(&self) -> Vec<FudgeRoll> {
+        self.iter()
+            .map(|roll| match roll {
+                FudgeRoll::Plus => FudgeRoll::Minus,
+                FudgeRoll::Blank => FudgeRoll::Blank,
+                FudgeRoll::Minus => FudgeRoll::Plus,
+            })
+            .collect()
+    }
+}
+impl<T: RollBounds, V: DiceBounds> Negate<Rolls<T, V>> for Rolls<T, V>
+where
+    Vec<T>: Negate<Vec<T>>,
+{
+    fn negate(&self) -> Rolls<T, V> {
+        Rolls {
+            description: format!("-({})", &self.description),
+            dice: self.dice.clone(),
+            id: self.id.clone(),
+            rolls: self.rolls.negate(),
+            history: self.history.clone(),
+        }
+    }
+}
+
 /// Explode rerolls the dice whenever the roll is equals to one of the action parameters.
 /// The new rolls can also trigger an explosion.
 ///
@@ -270,20 +527,24 @@ impl Concat<NumericRolls> for NumericRolls {
 ///     NumericDice::RepeatingDice(vec![1, 2, 3, 2, 1]),
 /// );
 /// let dice = DiceGenerator::new();
-/// let rolls = NumericRolls::new(dice_request, &dice);
+/// let rolls = NumericRolls::new(dice_request, &dice).unwrap();
 /// let expected = vec![1, 2, 3, 2, 1, 1, 2, 1];
-/// assert_eq!(rolls.explode(&dice, &vec![2, 5]).rolls, expected);
+/// assert_eq!(rolls.explode(&dice, &vec![2, 5]).unwrap().rolls, expected);
 /// ```
-/// # Warning
-/// Don't use on a [ConstDice](../dice/struct.ConstDice.html) result with the same ConstDice for rerolls: it would end in stack overflow since the highest value=only value will always be rerolled
+/// # Errors
+/// On a [ConstDice](../dice/enum.NumericDice.html) exploding on its only value, the explosions never stop:
+/// they fail when they go deeper than the [Limits](../dice/struct.Limits.html) `max_explosion_depth`.
 pub trait Explode<T: RollBounds, V: DiceBounds> {
-    fn explode(&self, dice: &Roll<T, V>, explosion_values: &Vec<T>) -> Rolls<T, V>;
+    fn explode(&self, dice: &dyn Roll<T, V>, explosion_values: &[T]) -> Result<Rolls<T, V>, Error>;
 }
 
 impl<T: RollBounds, V: DiceBounds> Explode<T, V> for Rolls<T, V> {
-    fn explode(&self, dice: &Roll<T, V>, explosion_values: &Vec<T>) -> Rolls<T, V> {
-        Rolls {
-            description: format!(
+    fn explode(&self, dice: &dyn Roll<T, V>, explosion_values: &[T]) -> Result<Rolls<T, V>, Error> {
+        self.add_explosions(
+            dice,
+            |roll| explosion_values.contains(&roll),
+            Ok,
+            format!(
                 "{} explode({})",
                 self.description,
                 &explosion_values
@@ -292,62 +553,203 @@ impl<T: RollBounds, V: DiceBounds> Explode<T, V> for Rolls<T, V> {
                     .collect::<Vec<String>>()
                     .join(",")
             ),
-            rolls: explode(&self.rolls, dice, &self.dice, explosion_values),
+        )
+    }
+}
+
+impl<T: RollBounds, V: DiceBounds> Rolls<T, V> {
+    /// Add a new die for each die whose roll `explodes`, the new die counting for `value(roll)`.
+    /// The new rolls can also trigger an explosion.
+    fn add_explosions<E, F>(
+        &self,
+        dice: &dyn Roll<T, V>,
+        explodes: E,
+        value: F,
+        description: String,
+    ) -> Result<Rolls<T, V>, Error>
+    where
+        E: Fn(T) -> bool,
+        F: Fn(T) -> Result<T, Error>,
+    {
+        let mut rolls = self.rolls.clone();
+        let mut history = self.history.clone();
+        // Each new batch of rolls has one die per exploding die of the previous batch
+        let mut parents: Vec<Option<usize>> = vec![];
+        let mut picked: Vec<usize> = vec![];
+        for roll in self.rolls.iter().filter(|roll| explodes(**roll)) {
+            let parent = self.find_die(*roll, &picked);
+            picked.extend(parent);
+            parents.push(parent);
+        }
+        let max_depth = dice.limits().max_explosion_depth;
+        let mut depth = 0;
+        while !parents.is_empty() {
+            depth += 1;
+            if depth > max_depth {
+                return Err(Error::limit_exceeded("successive explosions", max_depth));
+            }
+            let new_rolls = dice.roll(parents.len() as DiceNumber, &self.dice)?;
+            let mut next_parents = vec![];
+            for (roll, parent) in new_rolls.into_iter().zip(parents) {
+                if explodes(roll) {
+                    next_parents.push(Some(history.len()));
+                }
+                let value = value(roll)?;
+                let mut die = DieHistory::new(value);
+                die.exploded_from = parent;
+                history.push(die);
+                rolls.push(value);
+            }
+            parents = next_parents;
+        }
+        Ok(Rolls {
+            description,
+            rolls,
+            history,
             dice: self.dice.clone(),
+            id: self.id.clone(),
+        })
+    }
+}
+
+/// Compound adds a new roll to each roll matching the condition, instead of adding a new die
+/// like [Explode](trait.Explode.html). The new rolls can also trigger a compounding explosion.
+///
+/// # Example
+/// ```
+/// # use letsroll::actions::{Comparison, Compound, RollCondition};
+/// # use letsroll::dice::{DiceGenerator, NumericRolls, NumericDice, NumericRollRequest};
+/// let dice_request = NumericRollRequest::new(
+///     3,
+///     NumericDice::RepeatingDice(vec![3, 6, 1]),
+/// );
+/// let dice = DiceGenerator::new();
+/// let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+/// // The 6 gets a new roll of 3 added
+/// let condition = RollCondition::Threshold(Comparison::GreaterOrEqual, 5);
+/// assert_eq!(rolls.compound(&dice, &condition).unwrap().rolls, vec![3, 9, 1]);
+/// ```
+/// # Errors
+/// Like [Explode](trait.Explode.html), fails when the explosions go deeper than the
+/// [Limits](../dice/struct.Limits.html) `max_explosion_depth`.
+pub trait Compound<T> {
+    fn compound(
+        &self,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
+        condition: &RollCondition,
+    ) -> Result<T, Error>;
+}
+
+impl Compound<NumericRolls> for NumericRolls {
+    fn compound(
+        &self,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
+        condition: &RollCondition,
+    ) -> Result<NumericRolls, Error> {
+        let max_depth = dice.limits().max_explosion_depth;
+        let mut rolls = self.rolls.clone();
+        let mut history = self.history.clone();
+        let mut picked: Vec<usize> = vec![];
+        for roll in rolls.iter_mut().filter(|roll| condition.matches(**roll)) {
+            let mut parts = vec![*roll];
+            while condition.matches(parts[parts.len() - 1]) {
+                if parts.len() > max_depth {
+                    return Err(Error::limit_exceeded("successive explosions", max_depth));
+                }
+                parts.extend(dice.roll(1, &self.dice)?);
+            }
+            let total = checked_sum(&parts)?;
+            if let Some(index) = self.find_die(*roll, &picked) {
+                let die = &mut history[index];
+                die.values.pop();
+                die.values.push(total);
+                die.compounded = parts;
+                picked.push(index);
+            }
+            *roll = total;
         }
+        Ok(Rolls {
+            description: format!("{} compound({})", self.description, condition),
+            dice: self.dice.clone(),
+            id: self.id.clone(),
+            rolls,
+            history,
+        })
     }
 }
 
-fn explode<T: RollBounds, V: DiceBounds>(
-    rolls: &Vec<T>,
-    dice: &Roll<T, V>,
-    dicekind: &V,
-    explosion_values: &Vec<T>,
-) -> Vec<T> {
-    let mut rolls = rolls.clone();
-    if rolls.len() != 0 {
-        let new_rolls = dice.roll(
-            rolls
-                .iter()
-                .filter(|roll| explosion_values.contains(roll))
-                .count() as DiceNumber,
-            dicekind,
-        );
-        rolls.append(&mut explode(&new_rolls, dice, dicekind, explosion_values));
+/// Penetrate adds a new die for each roll matching the condition, like [Explode](trait.Explode.html),
+/// but each new die counts for one less than its roll. The new rolls (before the penalty) can also trigger a penetration.
+///
+/// # Example
+/// ```
+/// # use letsroll::actions::{Penetrate, RollCondition};
+/// # use letsroll::dice::{DiceGenerator, NumericRolls, NumericDice, NumericRollRequest};
+/// let dice_request = NumericRollRequest::new(
+///     2,
+///     NumericDice::RepeatingDice(vec![2, 6]),
+/// );
+/// let dice = DiceGenerator::new();
+/// let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+/// // The 6 is followed by a new roll of 2, counting for 1
+/// let penetrated = rolls.penetrate(&dice, &RollCondition::Values(vec![6])).unwrap();
+/// assert_eq!(penetrated.rolls, vec![2, 6, 1]);
+/// ```
+pub trait Penetrate<T> {
+    fn penetrate(
+        &self,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
+        condition: &RollCondition,
+    ) -> Result<T, Error>;
+}
+
+impl Penetrate<NumericRolls> for NumericRolls {
+    fn penetrate(
+        &self,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
+        condition: &RollCondition,
+    ) -> Result<NumericRolls, Error> {
+        self.add_explosions(
+            dice,
+            |roll| condition.matches(roll),
+            |roll: NumericRoll| {
+                roll.checked_sub(1)
+                    .ok_or_else(|| Error::bad_action_parameter("Arithmetic overflow"))
+            },
+            format!("{} penetrate({})", self.description, condition),
+        )
     }
-    rolls
 }
 
 /// Return a single sum of all rolls, regardless of dice kind
 ///
 /// To get the sums of each kind of dice separately, use [Sum](trait.Sum.html)
 pub trait TotalSum {
-    fn total(&self) -> NumericRolls;
+    fn total(&self) -> Result<NumericRolls, Error>;
 }
 impl TotalSum for Vec<NumericRolls> {
-    fn total(&self) -> NumericRolls {
-        // TODO ne pas recalculer les sous sommes à chaque fois...
+    fn total(&self) -> Result<NumericRolls, Error> {
+        let sums = self
+            .iter()
+            .map(|roll| checked_sum(&roll.rolls))
+            .collect::<Result<Vec<NumericRoll>, Error>>()?;
         let description = format!(
             "Detailed rolls\t: {}\nTOTAL SUM \t",
             self.iter()
-                .map(|roll| format!(
-                    "({}:{})",
-                    roll.description.clone(),
-                    roll.rolls.iter().sum::<NumericRoll>()
-                ))
+                .zip(sums.iter())
+                .map(|(roll, sum)| format!("({}:{})", describe_with_audit(roll), sum))
                 .collect::<Vec<String>>()
                 .join(" + ")
         );
-        let sum: NumericRoll = match self.len() {
-            0 => 0,
-            _ => self.iter().map(|roll| roll.rolls.clone()).flatten().sum(),
-        };
+        let sum = checked_sum(&sums)?;
 
-        Rolls {
+        Ok(Rolls {
             dice: NumericDice::AggregationResult,
+            id: None,
             description,
             rolls: vec![sum],
-        }
+            history: vec![],
+        })
     }
 }
 
@@ -383,7 +785,14 @@ impl KeepBest<NumericRolls> for NumericRolls {
         Ok(Rolls {
             description: format!("{} KeepBest({})", &self.description, keep),
             dice: self.dice.clone(),
+            id: self.id.clone(),
             rolls: self.rolls.keep_best(keep)?,
+            history: self.history_after_drop(
+                &self
+                    .rolls
+                    .keep_worst(self.rolls.len() as DiceNumber - keep)?,
+                Action::KeepBest(keep),
+            ),
         })
     }
 }
@@ -396,13 +805,13 @@ impl KeepBest<NumericRolls> for NumericRolls {
 /// let input_rolls = vec![5,1,10];
 /// let dice = DiceGenerator::new();
 /// let dice_request = NumericRollRequest::new(3, NumericDice::RepeatingDice(input_rolls));
-/// let rolls = NumericRolls::new(dice_request, &dice);
+/// let rolls = NumericRolls::new(dice_request, &dice).unwrap();
 /// assert_eq!(rolls.reroll_best(&dice, 1).unwrap().rolls, vec![1,5,5]);
 /// ```
 pub trait RerollBest<T> {
     fn reroll_best(
         &self,
-        dice: &Roll<NumericRoll, NumericDice>,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
         reroll: DiceNumber,
     ) -> Result<T, Error>;
 }
@@ -437,7 +846,14 @@ impl KeepWorst<NumericRolls> for NumericRolls {
         Ok(Rolls {
             description: format!("{} KeepWorst({})", &self.description, keep),
             dice: self.dice.clone(),
+            id: self.id.clone(),
             rolls: self.rolls.keep_worst(keep)?,
+            history: self.history_after_drop(
+                &self
+                    .rolls
+                    .keep_best(self.rolls.len() as DiceNumber - keep)?,
+                Action::KeepWorst(keep),
+            ),
         })
     }
 }
@@ -445,7 +861,7 @@ impl KeepWorst<NumericRolls> for NumericRolls {
 impl RerollBest<NumericRolls> for NumericRolls {
     fn reroll_best(
         &self,
-        dice: &Roll<NumericRoll, NumericDice>,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
         reroll: DiceNumber,
     ) -> Result<NumericRolls, Error> {
         if reroll as usize > self.rolls.len() {
@@ -458,12 +874,16 @@ impl RerollBest<NumericRolls> for NumericRolls {
         let mut rolls = self
             .rolls
             .keep_worst(self.rolls.len() as DiceNumber - reroll)?;
-        rolls.append(&mut dice.roll(reroll, &self.dice));
+        let rerolls = dice.roll(reroll, &self.dice)?;
+        let history = self.history_after_reroll(&self.rolls.keep_best(reroll)?, &rerolls);
+        rolls.extend(rerolls);
 
         Ok(Rolls {
             description: format!("{} RerollBest({})", &self.description, reroll),
             dice: self.dice.clone(),
-            rolls: rolls,
+            id: self.id.clone(),
+            rolls,
+            history,
         })
     }
 }
@@ -476,13 +896,13 @@ impl RerollBest<NumericRolls> for NumericRolls {
 /// let input_rolls = vec![5,1,10];
 /// let dice = DiceGenerator::new();
 /// let dice_request = NumericRollRequest::new(3, NumericDice::RepeatingDice(input_rolls));
-/// let rolls = NumericRolls::new(dice_request, &dice);
+/// let rolls = NumericRolls::new(dice_request, &dice).unwrap();
 /// assert_eq!(rolls.reroll_worst(&dice, 1).unwrap().rolls, vec![5, 10, 5]);
 /// ```
 pub trait RerollWorst<T> {
     fn reroll_worst(
         &self,
-        dice: &Roll<NumericRoll, NumericDice>,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
         reroll: DiceNumber,
     ) -> Result<T, Error>;
 }
@@ -490,7 +910,7 @@ pub trait RerollWorst<T> {
 impl RerollWorst<NumericRolls> for NumericRolls {
     fn reroll_worst(
         &self,
-        dice: &Roll<NumericRoll, NumericDice>,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
         reroll: DiceNumber,
     ) -> Result<NumericRolls, Error> {
         if reroll as usize > self.rolls.len() {
@@ -504,12 +924,16 @@ impl RerollWorst<NumericRolls> for NumericRolls {
         let mut rolls = self
             .rolls
             .keep_best(self.rolls.len() as DiceNumber - reroll)?;
-        rolls.append(&mut dice.roll(reroll, &self.dice));
+        let rerolls = dice.roll(reroll, &self.dice)?;
+        let history = self.history_after_reroll(&self.rolls.keep_worst(reroll)?, &rerolls);
+        rolls.extend(rerolls);
 
         Ok(Rolls {
             description: format!("{} RerollWorst({})", &self.description, reroll),
             dice: self.dice.clone(),
-            rolls: rolls,
+            id: self.id.clone(),
+            rolls,
+            history,
         })
     }
 }
@@ -527,11 +951,9 @@ pub trait CountValues {
 impl<T: RollBounds, V: DiceBounds> CountValues for TypedRollSession<T, V> {
     fn count(&self) -> NumericSession {
         let mut set: HashMap<&T, NumericRoll> = HashMap::new();
-        let all_rolls = self.rolls.iter().map(|rolls| &rolls.rolls).flatten();
+        let all_rolls = self.rolls.iter().flat_map(|rolls| &rolls.rolls);
         for roll in all_rolls {
-            set.entry(&roll)
-                .and_modify(|count| *count += 1)
-                .or_insert(1);
+            set.entry(roll).and_modify(|count| *count += 1).or_insert(1);
         }
         let rolls = set
             .iter()
@@ -539,43 +961,643 @@ impl<T: RollBounds, V: DiceBounds> CountValues for TypedRollSession<T, V> {
                 description: format!("COUNT({})", &keyval.0),
                 rolls: vec![*keyval.1],
                 dice: NumericDice::AggregationResult,
+                id: None,
+                history: vec![],
             })
             .collect();
         NumericSession {
             requests: vec![], // TODO surely not the correct answer
-            dice: DiceGenerator::new(),
-            rolls: rolls,
+            dice: self.dice.clone(),
+            rolls,
+            actions: vec![],
+        }
+    }
+}
+
+/// SumById will merge the rolls of all the dice sharing the same ID and sum them,
+/// then add the grand total of all the rolls.
+///
+/// For example, if given the following rolls:
+/// (FIRE 2D6): 3 5, (FORCE 1D4): 2, (FIRE 1D8): 7
+/// the returned sums will be:
+/// SUM(FIRE): 15, SUM(FORCE): 2, TOTAL: 17
+///
+/// Dice without ID are summed together under `SUM(untagged)`.
+/// # Example
+/// ```
+/// # use letsroll::actions::SumById;
+/// # use letsroll::dice::{NumericDice, NumericRollRequest};
+/// # use letsroll::NumericSession;
+/// let session = NumericSession::build(vec![
+///     NumericRollRequest::new(2, NumericDice::ConstDice(3)).add_id(Some(String::from("FIRE"))),
+///     NumericRollRequest::new(1, NumericDice::ConstDice(4)).add_id(Some(String::from("FORCE"))),
+///     NumericRollRequest::new(1, NumericDice::ConstDice(5)).add_id(Some(String::from("FIRE"))),
+/// ]);
+/// let sums: Vec<i32> = session.sum_by_id().unwrap().rolls.iter().map(|rolls| rolls.rolls[0]).collect();
+/// assert_eq!(sums, vec![11, 4, 15]);
+/// ```
+pub trait SumById {
+    fn sum_by_id(&self) -> Result<NumericSession, Error>;
+}
+
+impl SumById for NumericSession {
+    fn sum_by_id(&self) -> Result<NumericSession, Error> {
+        // Keep the order of first appearance of each ID
+        let mut sums: Vec<(&Option<DiceID>, NumericRoll)> = vec![];
+        for rolls in self.rolls.iter() {
+            let sum = checked_sum(&rolls.rolls)?;
+            match sums.iter_mut().find(|(id, _)| **id == rolls.id) {
+                Some((_, total)) => *total = checked_sum(&[*total, sum])?,
+                None => sums.push((&rolls.id, sum)),
+            }
+        }
+        let total = checked_sum(sums.iter().map(|(_, sum)| sum))?;
+        let mut rolls: Vec<NumericRolls> = sums
+            .into_iter()
+            .map(|(id, sum)| Rolls {
+                description: format!(
+                    "SUM({})",
+                    id.as_ref().map(|id| id.as_str()).unwrap_or("untagged")
+                ),
+                rolls: vec![sum],
+                dice: NumericDice::AggregationResult,
+                id: id.clone(),
+                history: vec![],
+            })
+            .collect();
+        rolls.push(Rolls {
+            description: String::from("TOTAL"),
+            rolls: vec![total],
+            dice: NumericDice::AggregationResult,
+            id: None,
+            history: vec![],
+        });
+        Ok(NumericSession {
+            requests: self.requests.clone(),
+            dice: self.dice.clone(),
+            rolls,
+            actions: vec![],
+        })
+    }
+}
+
+/// CancelSymbols counts the symbols of all the rolls, each symbol cancelling one of its opposite symbol
+/// (cf. [Symbol::opposite](../dice/enum.Symbol.html#method.opposite)).
+///
+/// The result is the net successes or failures, the net advantages or threats,
+/// then the triumphs and despairs if there are any. Triumphs count as successes too, and despairs as failures.
+/// # Example
+/// ```
+/// # use letsroll::actions::CancelSymbols;
+/// # use letsroll::dice::{Symbol, SymbolDice, SymbolRoll, SymbolRollRequest};
+/// # use letsroll::SymbolSession;
+/// let session = SymbolSession::build(vec![
+///     SymbolRollRequest::new(2, SymbolDice::ConstDice(SymbolRoll::double(Symbol::Success, Symbol::Advantage))),
+///     SymbolRollRequest::new(1, SymbolDice::ConstDice(SymbolRoll::double(Symbol::Failure, Symbol::Threat))),
+///     SymbolRollRequest::new(1, SymbolDice::ConstDice(SymbolRoll::single(Symbol::Despair))),
+/// ]);
+/// let results: Vec<(String, i32)> = session
+///     .cancel()
+///     .rolls
+///     .iter()
+///     .map(|rolls| (rolls.description.clone(), rolls.rolls[0]))
+///     .collect();
+/// assert_eq!(results, vec![
+///     (String::from("SUCCESS"), 0),
+///     (String::from("ADVANTAGE"), 1),
+///     (String::from("DESPAIR"), 1),
+/// ]);
+/// ```
+pub trait CancelSymbols {
+    fn cancel(&self) -> NumericSession;
+}
+
+impl CancelSymbols for SymbolSession {
+    fn cancel(&self) -> NumericSession {
+        let mut counts: HashMap<Symbol, NumericRoll> = HashMap::new();
+        let all_symbols = self
+            .rolls
+            .iter()
+            .flat_map(|rolls| rolls.rolls.iter())
+            .flat_map(|roll| roll.symbols());
+        for symbol in all_symbols {
+            *counts.entry(symbol).or_insert(0) += 1;
+        }
+        let count = |symbol| *counts.get(&symbol).unwrap_or(&0);
+        // A negative net count of a symbol is a positive count of its opposite
+        let net = |symbol: Symbol, net: NumericRoll| match (net, symbol.opposite()) {
+            (net, Some(opposite)) if net < 0 => (opposite, -net),
+            _ => (symbol, net),
+        };
+        let successes = count(Symbol::Success) + count(Symbol::Triumph)
+            - count(Symbol::Failure)
+            - count(Symbol::Despair);
+        let advantages = count(Symbol::Advantage) - count(Symbol::Threat);
+        let mut results = vec![
+            net(Symbol::Success, successes),
+            net(Symbol::Advantage, advantages),
+        ];
+        results.extend(
+            [Symbol::Triumph, Symbol::Despair]
+                .iter()
+                .filter(|symbol| count(**symbol) > 0)
+                .map(|symbol| (*symbol, count(*symbol))),
+        );
+        NumericSession {
+            requests: vec![],
+            dice: self.dice.clone(),
+            rolls: results
+                .into_iter()
+                .map(|(symbol, count)| Rolls {
+                    description: symbol.to_string().to_uppercase(),
+                    rolls: vec![count],
+                    dice: NumericDice::AggregationResult,
+                    id: None,
+                    history: vec![],
+                })
+                .collect(),
+            actions: vec![],
+        }
+    }
+}
+
+/// Count the rolls that are successes against the target value, minus the botches.
+///
+/// A roll equal to one of the botch values removes one success, so the result can be negative.
+/// # Example
+/// ```
+/// # use letsroll::actions::{Comparison, CountSuccesses};
+/// # use letsroll::dice::{DiceGenerator, NumericRolls, NumericDice, NumericRollRequest};
+/// let input_rolls = vec![10, 8, 3, 1, 5, 9];
+/// let dice_request = NumericRollRequest::new(6, NumericDice::RepeatingDice(input_rolls));
+/// let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
+/// assert_eq!(rolls.count_successes(Comparison::GreaterOrEqual, 8, &[]).rolls, vec![3]);
+/// assert_eq!(rolls.count_successes(Comparison::GreaterOrEqual, 8, &[1]).rolls, vec![2]);
+/// ```
+pub trait CountSuccesses<T> {
+    fn count_successes(
+        &self,
+        comparison: Comparison,
+        target: NumericRoll,
+        botch: &[NumericRoll],
+    ) -> T;
+}
+impl CountSuccesses<NumericRolls> for NumericRolls {
+    fn count_successes(
+        &self,
+        comparison: Comparison,
+        target: NumericRoll,
+        botch: &[NumericRoll],
+    ) -> NumericRolls {
+        let successes = self
+            .rolls
+            .iter()
+            .filter(|roll| comparison.compare(**roll, target))
+            .count();
+        let botches = self
+            .rolls
+            .iter()
+            .filter(|roll| botch.contains(roll))
+            .count();
+        let botch_description = match botch.len() {
+            0 => String::from(""),
+            _ => format!(
+                " Botch({})",
+                botch
+                    .iter()
+                    .map(|val| val.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+        };
+        let botches_description = match botch.len() {
+            0 => String::from(""),
+            _ => format!(", {} botch(es)", botches),
+        };
+        Rolls {
+            description: format!(
+                "{} Successes({}{}){} ({} => {} success(es), {} failure(s){})",
+                self.description,
+                comparison,
+                target,
+                botch_description,
+                self.rolls
+                    .iter()
+                    .map(|roll| roll.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" "),
+                successes,
+                self.rolls.len() - successes,
+                botches_description
+            ),
+            dice: NumericDice::AggregationResult,
+            id: self.id.clone(),
+            rolls: vec![successes as NumericRoll - botches as NumericRoll],
+            history: self.history.clone(),
+        }
+    }
+}
+
+/// Keep the best or the worst of two rolls of the same dice group, by comparing their totals.
+///
+/// The dice of the discarded rolls are kept in the history, dropped by the action, so that both rolls are shown.
+/// On a tie, the first rolls are kept.
+/// # Example
+/// ```
+/// # use letsroll::actions::Advantage;
+/// # use letsroll::dice::{DiceGenerator, NumericRolls, NumericDice, NumericRollRequest};
+/// let dice = DiceGenerator::new();
+/// let first = NumericRolls::new(NumericRollRequest::new(1, NumericDice::ConstDice(7)), &dice).unwrap();
+/// let second = NumericRolls::new(NumericRollRequest::new(1, NumericDice::ConstDice(15)), &dice).unwrap();
+/// assert_eq!(first.advantage(&second).rolls, vec![15]);
+/// assert_eq!(first.disadvantage(&second).rolls, vec![7]);
+/// assert_eq!(first.advantage(&second).history.len(), 2);
+/// ```
+pub trait Advantage<T: RollBounds, V: DiceBounds> {
+    fn advantage(&self, other: &Rolls<T, V>) -> Rolls<T, V>;
+
+    fn disadvantage(&self, other: &Rolls<T, V>) -> Rolls<T, V>;
+}
+impl Advantage<NumericRoll, NumericDice> for NumericRolls {
+    fn advantage(&self, other: &NumericRolls) -> NumericRolls {
+        // Compare in i64, where the sums of up to 255 rolls cannot overflow
+        let total: i64 = self.rolls.iter().map(|roll| i64::from(*roll)).sum();
+        let other_total: i64 = other.rolls.iter().map(|roll| i64::from(*roll)).sum();
+        if total >= other_total {
+            self.keep_over(other, Action::Advantage)
+        } else {
+            other.keep_over(self, Action::Advantage)
+        }
+    }
+
+    fn disadvantage(&self, other: &NumericRolls) -> NumericRolls {
+        let total: i64 = self.rolls.iter().map(|roll| i64::from(*roll)).sum();
+        let other_total: i64 = other.rolls.iter().map(|roll| i64::from(*roll)).sum();
+        if total <= other_total {
+            self.keep_over(other, Action::Disadvantage)
+        } else {
+            other.keep_over(self, Action::Disadvantage)
+        }
+    }
+}
+
+impl<T: RollBounds, V: DiceBounds> Rolls<T, V> {
+    /// Keep these rolls, followed in the history by the dice of the `discarded` rolls.
+    fn keep_over(&self, discarded: &Rolls<T, V>, action: Action) -> Rolls<T, V> {
+        let offset = self.history.len();
+        let mut history = self.history.clone();
+        history.extend(discarded.history.iter().map(|die| {
+            let mut die = die.clone();
+            die.exploded_from = die.exploded_from.map(|index| index + offset);
+            if die.is_kept() {
+                die.dropped_by = Some(action.clone());
+            }
+            die
+        }));
+        Rolls {
+            description: format!("{} {}", self.description, action),
+            dice: self.dice.clone(),
+            id: self.id.clone(),
+            rolls: self.rolls.clone(),
+            history,
+        }
+    }
+}
+
+/// Flag the natural rolls of the kept dice: the ones matching the condition (the highest face by default)
+/// are critical successes, and the lowest face is a critical failure.
+///
+/// Constant dice are modifiers, so they are never critical.
+/// # Example
+/// ```
+/// # use letsroll::actions::DetectCritical;
+/// # use letsroll::dice::{Critical, DiceGenerator, NumericRolls, NumericDice, NumericRollRequest};
+/// let dice = DiceGenerator::new();
+/// let request = NumericRollRequest::new(3, NumericDice::RepeatingDice(vec![20, 1, 19]));
+/// let rolls = NumericRolls::new(request, &dice).unwrap().detect_critical(None);
+/// assert_eq!(rolls.history[0].critical, Some(Critical::Success));
+/// assert_eq!(rolls.history[1].critical, Some(Critical::Failure));
+/// assert_eq!(rolls.history[2].critical, None);
+/// assert_eq!(rolls.critical(), Some(Critical::Success));
+/// ```
+pub trait DetectCritical<T> {
+    fn detect_critical(&self, condition: Option<&RollCondition>) -> T;
+}
+impl DetectCritical<NumericRolls> for NumericRolls {
+    fn detect_critical(&self, condition: Option<&RollCondition>) -> NumericRolls {
+        let mut history = self.history.clone();
+        if let Some((lowest, highest)) = self.dice.natural_range() {
+            for die in history.iter_mut().filter(|die| die.is_kept()) {
+                let value = die.value();
+                let success = match condition {
+                    Some(condition) => condition.matches(value),
+                    None => value == highest,
+                };
+                die.critical = if success {
+                    Some(Critical::Success)
+                } else if value == lowest {
+                    Some(Critical::Failure)
+                } else {
+                    None
+                };
+            }
+        }
+        let description = match condition {
+            Some(condition) => format!("{} Crit({})", self.description, condition),
+            None => format!("{} Crit", self.description),
+        };
+        Rolls {
+            description,
+            history,
+            ..self.clone()
+        }
+    }
+}
+
+/// Bonus and penalty dice of the d100 systems: each percentile roll gets N more tens dice,
+/// and the best (lowest) or the worst (highest) of the resulting rolls is kept, with the same units die.
+///
+/// The extra tens dice are 10-sided dice, 10 standing for 00.
+/// The discarded rolls are kept in the history of the die, before its current value.
+/// # Example
+/// ```
+/// # use letsroll::actions::BonusDice;
+/// # use letsroll::dice::{percentile_digits, DiceGenerator, NumericRolls, NumericDice, NumericRollRequest};
+/// let dice = DiceGenerator::from_seed(42);
+/// let rolls = NumericRolls::new(NumericRollRequest::new(1, NumericDice::PercentileDice), &dice).unwrap();
+/// let bonus = rolls.bonus(&dice, 2).unwrap();
+/// assert!(bonus.rolls[0] <= rolls.rolls[0]);
+/// assert_eq!(percentile_digits(bonus.rolls[0]).1, percentile_digits(rolls.rolls[0]).1);
+/// assert_eq!(bonus.history[0].values.len(), 3);
+/// ```
+pub trait BonusDice<T> {
+    fn bonus(&self, dice: &dyn Roll<NumericRoll, NumericDice>, n: DiceNumber) -> Result<T, Error>;
+
+    fn penalty(&self, dice: &dyn Roll<NumericRoll, NumericDice>, n: DiceNumber)
+        -> Result<T, Error>;
+}
+impl BonusDice<NumericRolls> for NumericRolls {
+    fn bonus(
+        &self,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
+        n: DiceNumber,
+    ) -> Result<NumericRolls, Error> {
+        self.roll_tens_dice(dice, Action::Bonus(n))
+    }
+
+    fn penalty(
+        &self,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
+        n: DiceNumber,
+    ) -> Result<NumericRolls, Error> {
+        self.roll_tens_dice(dice, Action::Penalty(n))
+    }
+}
+
+impl NumericRolls {
+    /// Roll the extra tens dice of a [Bonus](enum.Action.html#variant.Bonus) or a [Penalty](enum.Action.html#variant.Penalty) action.
+    fn roll_tens_dice(
+        &self,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
+        action: Action,
+    ) -> Result<NumericRolls, Error> {
+        let (n, bonus) = match action {
+            Action::Bonus(n) => (n, true),
+            Action::Penalty(n) => (n, false),
+            _ => unreachable!(),
+        };
+        if self.dice != NumericDice::PercentileDice {
+            return Err(Error::incompatible(
+                &action.to_string(),
+                "non percentile roll",
+            ));
+        }
+        let mut rolls = self.rolls.clone();
+        let mut history = self.history.clone();
+        let mut picked: Vec<usize> = vec![];
+        for roll in rolls.iter_mut() {
+            let (_, units) = percentile_digits(*roll);
+            let mut candidates = vec![*roll];
+            for tens in dice.roll(n, &NumericDice::NumberedDice(10))? {
+                candidates.push(percentile_roll(tens % 10 * 10, units));
+            }
+            let kept = if bonus {
+                *candidates.iter().min().unwrap()
+            } else {
+                *candidates.iter().max().unwrap()
+            };
+            // The kept roll is the current value of the die
+            let position = candidates.iter().position(|roll| *roll == kept).unwrap();
+            candidates.remove(position);
+            candidates.push(kept);
+            if let Some(index) = self.find_die(*roll, &picked) {
+                let die = &mut history[index];
+                die.values.pop();
+                die.values.extend(candidates);
+                picked.push(index);
+            }
+            *roll = kept;
+        }
+        Ok(Rolls {
+            description: format!("{} {}", self.description, action),
+            dice: self.dice.clone(),
+            id: self.id.clone(),
+            rolls,
+            history,
+        })
+    }
+}
+
+/// Success level of a roll against a skill value, in the d100 systems like Call of Cthulhu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub enum SkillLevel {
+    Failure,
+    Regular,
+    Hard,
+    Extreme,
+}
+
+impl SkillLevel {
+    /// Level of the roll: extreme up to a fifth of the skill, hard up to half of it and regular up to the skill.
+    /// A fumble is always a failure.
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::actions::SkillLevel;
+    /// assert_eq!(SkillLevel::of(12, 60), SkillLevel::Extreme);
+    /// assert_eq!(SkillLevel::of(30, 60), SkillLevel::Hard);
+    /// assert_eq!(SkillLevel::of(60, 60), SkillLevel::Regular);
+    /// assert_eq!(SkillLevel::of(61, 60), SkillLevel::Failure);
+    /// assert_eq!(SkillLevel::of(100, 100), SkillLevel::Failure);
+    /// ```
+    pub fn of(roll: NumericRoll, skill: NumericRoll) -> SkillLevel {
+        if SkillLevel::is_fumble(roll, skill) || roll > skill {
+            SkillLevel::Failure
+        } else if roll <= skill / 5 {
+            SkillLevel::Extreme
+        } else if roll <= skill / 2 {
+            SkillLevel::Hard
+        } else {
+            SkillLevel::Regular
+        }
+    }
+
+    /// A roll of 100 is a fumble, and so are the rolls from 96 when the skill is under 50.
+    pub fn is_fumble(roll: NumericRoll, skill: NumericRoll) -> bool {
+        roll >= 100 || (skill < 50 && roll >= 96)
+    }
+}
+
+/// Replace the rolls by their [success level](enum.SkillLevel.html) against a skill value
+/// (0 for a failure, 1 for a regular success, 2 for a hard one and 3 for an extreme one).
+///
+/// The natural rolls of 1 are flagged as critical successes, and the fumbles as critical failures.
+/// The levels are not numbers: a request can't total them afterwards.
+/// # Example
+/// ```
+/// # use letsroll::actions::SkillCheck;
+/// # use letsroll::dice::{Critical, DiceGenerator, NumericRolls, NumericDice, NumericRollRequest};
+/// let dice_request = NumericRollRequest::new(4, NumericDice::RepeatingDice(vec![1, 45, 70, 98]));
+/// let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
+/// let levels = rolls.skill_check(45);
+/// assert_eq!(levels.rolls, vec![3, 1, 0, 0]);
+/// assert_eq!(levels.history[0].critical, Some(Critical::Success));
+/// assert_eq!(levels.history[3].critical, Some(Critical::Failure));
+/// ```
+pub trait SkillCheck<T> {
+    fn skill_check(&self, skill: NumericRoll) -> T;
+}
+impl SkillCheck<NumericRolls> for NumericRolls {
+    fn skill_check(&self, skill: NumericRoll) -> NumericRolls {
+        let mut history = self.history.clone();
+        if self.dice.natural_range().is_some() {
+            for die in history.iter_mut().filter(|die| die.is_kept()) {
+                if die.value() == 1 {
+                    die.critical = Some(Critical::Success);
+                } else if SkillLevel::is_fumble(die.value(), skill) {
+                    die.critical = Some(Critical::Failure);
+                }
+            }
+        }
+        let levels: Vec<SkillLevel> = self
+            .rolls
+            .iter()
+            .map(|roll| SkillLevel::of(*roll, skill))
+            .collect();
+        Rolls {
+            description: format!(
+                "{} Skill({}) ({} => {})",
+                self.description,
+                skill,
+                self.rolls
+                    .iter()
+                    .map(|roll| roll.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" "),
+                levels
+                    .iter()
+                    .map(|level| format!("{:?}", level))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            dice: NumericDice::AggregationResult,
+            id: self.id.clone(),
+            rolls: levels
+                .into_iter()
+                .map(|level| level as NumericRoll)
+                .collect(),
+            history,
         }
     }
 }
 
 pub trait Apply<T: RollBounds, V: DiceBounds> {
-    fn apply(&self, action: &Action, dice: &Roll<T, V>) -> Result<Rolls<T, V>, Error>;
+    fn apply(&self, action: &Action, dice: &dyn Roll<T, V>) -> Result<Rolls<T, V>, Error>;
+
+    /// Apply an action rolling the dice group twice (cf. [Action::rolls_group_twice](enum.Action.html#method.rolls_group_twice)),
+    /// given the `other` rolls of the same group.
+    fn apply_twice(&self, action: &Action, other: &Rolls<T, V>) -> Result<Rolls<T, V>, Error>;
 }
 
 impl Apply<NumericRoll, NumericDice> for NumericRolls {
     fn apply(
         &self,
         action: &Action,
-        dice: &Roll<NumericRoll, NumericDice>,
+        dice: &dyn Roll<NumericRoll, NumericDice>,
     ) -> Result<NumericRolls, Error> {
         match action {
-            Action::Sum => Ok(self.sum()),
+            Action::Sum => {
+                checked_sum(&self.rolls)?;
+                Ok(self.sum())
+            }
             Action::Concat => self.concat(),
-            Action::MultiplyBy(factor) => Ok(self.multiply(*factor)),
-            Action::Explode(explosion_value) => Ok(self.explode(dice, &explosion_value)),
-            Action::FlipFlop => Ok(self.flip()),
-            Action::RerollNumeric(values_to_reroll) => Ok(self.reroll(dice, &values_to_reroll)),
-            Action::RerollFudge(_) | Action::ExplodeFudge(_) | Action::Total => {
-                return Err(Error::incompatible(
-                    &action.to_string(),
-                    &String::from("numeric roll"),
-                ));
+            Action::MultiplyBy(factor) => {
+                if self
+                    .rolls
+                    .iter()
+                    .any(|roll| roll.checked_mul(*factor).is_none())
+                {
+                    return Err(Error::bad_action_parameter("Arithmetic overflow"));
+                }
+                Ok(self.multiply(*factor))
+            }
+            Action::Explode(condition) => self.add_explosions(
+                dice,
+                |roll| condition.matches(roll),
+                Ok,
+                format!("{} explode({})", self.description, condition),
+            ),
+            Action::Compound(condition) => self.compound(dice, condition),
+            Action::Penetrate(condition) => self.penetrate(dice, condition),
+            Action::FlipFlop => self.flip(),
+            Action::Negate => {
+                if self.rolls.iter().any(|roll| roll.checked_neg().is_none()) {
+                    return Err(Error::bad_action_parameter("Arithmetic overflow"));
+                }
+                Ok(self.negate())
             }
+            Action::RerollNumeric(condition, mode) => self.reroll_when(
+                dice,
+                |roll| condition.matches(roll),
+                *mode,
+                reroll_description(&self.description, *mode, condition),
+            ),
+            Action::RerollFudge(_, _) | Action::ExplodeFudge(_) | Action::Total => Err(
+                Error::incompatible(&action.to_string(), &String::from("numeric roll")),
+            ),
             Action::KeepBest(keep) => self.keep_best(*keep),
             Action::KeepWorst(keep) => self.keep_worst(*keep),
             Action::RerollBest(keep) => self.reroll_best(dice, *keep),
             Action::RerollWorst(keep) => self.reroll_worst(dice, *keep),
+            Action::CountSuccesses(comparison, target, botch) => {
+                Ok(self.count_successes(*comparison, *target, botch))
+            }
+            Action::Critical(condition) => Ok(self.detect_critical(condition.as_ref())),
+            Action::Bonus(n) => self.bonus(dice, *n),
+            Action::Penalty(n) => self.penalty(dice, *n),
+            Action::SkillCheck(skill) => Ok(self.skill_check(*skill)),
+            // The dice were already doubled when the request was rolled
+            Action::CriticalDamage => Ok(Rolls {
+                description: format!("{} CritDamage", self.description),
+                ..self.clone()
+            }),
+            // The dice group must be rolled again, with the actions applied before this one
+            Action::Advantage | Action::Disadvantage => Err(Error::incompatible(
+                &action.to_string(),
+                &String::from("rolls without their request"),
+            )),
+        }
+    }
+
+    fn apply_twice(&self, action: &Action, other: &NumericRolls) -> Result<NumericRolls, Error> {
+        match action {
+            Action::Advantage => Ok(self.advantage(other)),
+            Action::Disadvantage => Ok(self.disadvantage(other)),
+            _ => Err(Error::bad_action_parameter(&format!(
+                "{} doesn't roll the dice group twice",
+                action
+            ))),
         }
     }
 }
@@ -584,31 +1606,76 @@ impl Apply<FudgeRoll, FudgeDice> for FudgeRolls {
     fn apply(
         &self,
         action: &Action,
-        dice: &Roll<FudgeRoll, FudgeDice>,
+        dice: &dyn Roll<FudgeRoll, FudgeDice>,
     ) -> Result<FudgeRolls, Error> {
         match action {
-            Action::ExplodeFudge(explosion_value) => Ok(self.explode(dice, &explosion_value)),
-            Action::RerollFudge(values_to_reroll) => Ok(self.reroll(dice, &values_to_reroll)),
+            Action::ExplodeFudge(explosion_value) => self.explode(dice, explosion_value),
+            Action::RerollFudge(values_to_reroll, RerollMode::Once) => {
+                self.reroll(dice, values_to_reroll)
+            }
+            Action::RerollFudge(values_to_reroll, RerollMode::Until) => {
+                self.reroll_until(dice, values_to_reroll)
+            }
+            Action::Negate => Ok(self.negate()),
             Action::Sum
             | Action::Total
             | Action::Concat
             | Action::MultiplyBy(_)
             | Action::FlipFlop
-            | Action::RerollNumeric(_)
+            | Action::RerollNumeric(_, _)
             | Action::KeepBest(_)
             | Action::KeepWorst(_)
             | Action::RerollBest(_)
             | Action::RerollWorst(_)
-            | Action::Explode(_) => Err(Error::incompatible(
+            | Action::CountSuccesses(_, _, _)
+            | Action::Explode(_)
+            | Action::Compound(_)
+            | Action::Penetrate(_)
+            | Action::Advantage
+            | Action::Disadvantage
+            | Action::Critical(_)
+            | Action::CriticalDamage
+            | Action::Bonus(_)
+            | Action::Penalty(_)
+            | Action::SkillCheck(_) => Err(Error::incompatible(
                 &action.to_string(),
                 &String::from("fudge roll"),
             )),
         }
     }
+
+    fn apply_twice(&self, action: &Action, _other: &FudgeRolls) -> Result<FudgeRolls, Error> {
+        Err(Error::incompatible(
+            &action.to_string(),
+            &String::from("fudge roll"),
+        ))
+    }
+}
+
+/// Symbol rolls are only aggregated (cf. [CancelSymbols](trait.CancelSymbols.html)): no action applies to them.
+impl Apply<SymbolRoll, SymbolDice> for SymbolRolls {
+    fn apply(
+        &self,
+        action: &Action,
+        _dice: &dyn Roll<SymbolRoll, SymbolDice>,
+    ) -> Result<SymbolRolls, Error> {
+        Err(Error::incompatible(
+            &action.to_string(),
+            &String::from("symbol roll"),
+        ))
+    }
+
+    fn apply_twice(&self, action: &Action, _other: &SymbolRolls) -> Result<SymbolRolls, Error> {
+        Err(Error::incompatible(
+            &action.to_string(),
+            &String::from("symbol roll"),
+        ))
+    }
 }
 #[cfg(test)]
 mod tests {
     use crate::actions::*;
+    use crate::errors::ErrorKind;
     use std::str::FromStr;
 
     static NUM_INPUT: &[NumericRoll] = &[1, 1, 1, 15, 100];
@@ -623,11 +1690,12 @@ mod tests {
         let rolls_result = NumericRolls::new(
             RollRequest::new(5, NumericDice::RepeatingDice(input)),
             &DiceGenerator::new(),
-        );
+        )
+        .unwrap();
         let output = rolls_result.multiply(factor);
         assert_eq!(output.rolls.len(), expected.len());
-        for i in 0..expected.len() - 1 {
-            assert_eq!(output.rolls[i], expected[i] * factor);
+        for (roll, expected_roll) in output.rolls.iter().zip(expected.iter()) {
+            assert_eq!(*roll, expected_roll * factor);
         }
     }
 
@@ -636,10 +1704,26 @@ mod tests {
         let input = NUM_INPUT.to_vec();
         let dice_request =
             NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
-        let rolls = NumericRolls::new(dice_request, &DiceGenerator::new());
-        let output = rolls.flip();
+        let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
+        let output = rolls.flip().unwrap();
         let expected = vec![100, 100, 100, 510, 1];
         assert_eq!(output.rolls, expected);
+
+        // The padding follows the highest face
+        let dice_request =
+            NumericRollRequest::new(3, NumericDice::CustomDice(vec![7, 7, 7, 7, 7, 7, 7, 7, 70]));
+        let rolls = NumericRolls::new(dice_request, &DiceGenerator::from_seed(3)).unwrap();
+        let output = rolls.flip().unwrap();
+        assert!(output
+            .rolls
+            .iter()
+            .zip(rolls.rolls.iter())
+            .all(|(flipped, roll)| *flipped == if *roll == 7 { 70 } else { 7 }));
+
+        // The lowest roll has no positive counterpart to flip
+        let dice_request = NumericRollRequest::new(1, NumericDice::ConstDice(NumericRoll::MIN));
+        let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
+        assert!(rolls.flip().is_err());
     }
 
     #[test]
@@ -647,18 +1731,39 @@ mod tests {
         let input = NUM_INPUT.to_vec();
         let dice_request =
             NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
-        let rolls = NumericRolls::new(dice_request, &DiceGenerator::new());
+        let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
         let output = rolls.concat();
         let expected = vec![11115100];
         assert_eq!(output.unwrap().rolls, expected);
     }
 
+    #[test]
+    fn transform_negative_rolls() {
+        let input = vec![-3, 5, -12];
+        let dice_request =
+            NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
+        let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
+        assert_eq!(rolls.negate().rolls, vec![3, -5, 12]);
+        assert_eq!(rolls.negate().description, "-(3[-3,5,-12,...])");
+        assert_eq!(rolls.sum().rolls, vec![-10]);
+        assert_eq!(rolls.multiply(-2).rolls, vec![6, -10, 24]);
+        assert_eq!(rolls.keep_best(2).unwrap().rolls, vec![-3, 5]);
+        assert_eq!(rolls.keep_worst(1).unwrap().rolls, vec![-12]);
+        assert_eq!(rolls.flip().unwrap().rolls, vec![-3, 5, -21]);
+        assert_eq!(
+            *rolls.concat().unwrap_err().kind(),
+            ErrorKind::IncompatibleAction(String::from(
+                "Action \"Concat\" not supported by roll type \"negative numeric roll\""
+            ))
+        );
+    }
+
     #[test]
     fn transform_sum() {
         let input = NUM_INPUT.to_vec();
         let dice_request =
             NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
-        let rolls = NumericRolls::new(dice_request, &DiceGenerator::new());
+        let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
         let output = rolls.sum();
         let expected = vec![118];
         assert_eq!(output.rolls, expected);
@@ -670,20 +1775,119 @@ mod tests {
         let dice_request =
             NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
         let dice = DiceGenerator::new();
-        let rolls = NumericRolls::new(dice_request, &dice);
-        let output = rolls.reroll(&dice, &vec![100]);
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        let output = rolls.reroll(&dice, &[100]).unwrap();
         let expected = vec![1, 1, 1, 15, 1];
         assert_eq!(output.rolls, expected);
     }
 
+    #[test]
+    fn transform_reroll_until() {
+        let input = vec![2, 1, 3];
+        let dice_request =
+            NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
+        let dice = DiceGenerator::new();
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        // Each new roll is a 2
+        let output = rolls.reroll_until(&dice, &[1]).unwrap();
+        assert_eq!(output.rolls, vec![2, 2, 3]);
+        assert_eq!(output.history[1].values, vec![1, 2]);
+
+        let action = Action::RerollNumeric(
+            RollCondition::Threshold(Comparison::Lower, 3),
+            RerollMode::Once,
+        );
+        let output = rolls.apply(&action, &dice).unwrap();
+        assert_eq!(output.rolls, vec![2, 2, 3]);
+        assert_eq!(output.description, "3[2,1,3,...] Reroll(<3)");
+
+        // The new rolls keep matching the condition
+        let action = Action::RerollNumeric(RollCondition::Range(1, 2), RerollMode::Until);
+        match rolls.apply(&action, &dice).unwrap_err().kind() {
+            ErrorKind::LimitExceeded(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn transform_advantage() {
+        let dice = DiceGenerator::from_seed(4);
+        let request = NumericRollRequest::new(2, NumericDice::NumberedDice(6))
+            .add_action(Action::RerollNumeric(
+                RollCondition::Values(vec![1]),
+                RerollMode::Once,
+            ))
+            .add_action(Action::Advantage);
+        let rolls = request.roll(&dice).unwrap();
+        assert_eq!(rolls.description, "2D6 Reroll(1) Advantage");
+        // The dice of both rolls are kept in the history, the second ones dropped
+        assert_eq!(rolls.history.len(), 4);
+        let kept: NumericRoll = rolls.history[..2].iter().map(|die| die.value()).sum();
+        let dropped: NumericRoll = rolls.history[2..].iter().map(|die| die.value()).sum();
+        assert_eq!(rolls.rolls.iter().sum::<NumericRoll>(), kept);
+        assert!(kept >= dropped);
+        assert!(rolls.history[2..]
+            .iter()
+            .all(|die| die.dropped_by == Some(Action::Advantage)));
+
+        // Without its request, the group can't be rolled again
+        assert!(rolls.apply(&Action::Disadvantage, &dice).is_err());
+
+        // The groups are compared even when their sums don't fit in a roll
+        let request = NumericRollRequest::new(2, NumericDice::ConstDice(NumericRoll::MAX))
+            .add_action(Action::Advantage);
+        let rolls = request.roll(&dice).unwrap();
+        assert_eq!(rolls.rolls, vec![NumericRoll::MAX; 2]);
+    }
+
+    #[test]
+    fn transform_critical() {
+        let input = vec![20, 1, 19, 1];
+        let dice_request =
+            NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
+        let dice = DiceGenerator::new();
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        let condition = RollCondition::Threshold(Comparison::GreaterOrEqual, 19);
+        let output = rolls
+            .keep_best(3)
+            .unwrap()
+            .detect_critical(Some(&condition));
+        assert_eq!(
+            output.description,
+            "4[20,1,19,1,...] KeepBest(3) Crit(>=19)"
+        );
+        let statuses: Vec<Option<Critical>> =
+            output.history.iter().map(|die| die.critical).collect();
+        // The dropped die is not checked
+        assert_eq!(
+            statuses,
+            vec![
+                Some(Critical::Success),
+                None,
+                Some(Critical::Success),
+                Some(Critical::Failure)
+            ]
+        );
+        assert_eq!(output.critical(), Some(Critical::Success));
+
+        // Constant modifiers are never critical
+        let dice_request = NumericRollRequest::new(1, NumericDice::ConstDice(20));
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        assert_eq!(rolls.detect_critical(None).critical(), None);
+        assert_eq!(
+            rolls.apply(&Action::CriticalDamage, &dice).unwrap().rolls,
+            vec![20]
+        );
+    }
+
     #[test]
     fn transform_reroll_fudge() {
         let input = vec![FudgeRoll::Blank, FudgeRoll::Plus, FudgeRoll::Minus];
         let dice_request =
             FudgeRollRequest::new(input.len() as DiceNumber, FudgeDice::RepeatingDice(input));
         let dice = DiceGenerator::new();
-        let rolls = FudgeRolls::new(dice_request, &dice);
-        let output = rolls.reroll(&dice, &vec![FudgeRoll::Minus]);
+        let rolls = FudgeRolls::new(dice_request, &dice).unwrap();
+        let output = rolls.reroll(&dice, &[FudgeRoll::Minus]).unwrap();
         let expected = vec![FudgeRoll::Blank, FudgeRoll::Plus, FudgeRoll::Blank];
         assert_eq!(output.rolls, expected);
     }
@@ -694,26 +1898,141 @@ mod tests {
         let dice_request =
             NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
         let dice = DiceGenerator::new();
-        let rolls = NumericRolls::new(dice_request, &dice);
-        let output = rolls.explode(&dice, &vec![2, 3]);
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        let output = rolls.explode(&dice, &[2, 3]).unwrap();
         let expected = vec![1, 2, 3, 2, 1, 1, 2, 3, 1, 2, 1];
         assert_eq!(output.rolls, expected);
     }
 
+    #[test]
+    fn transform_limits() {
+        let limits = Limits {
+            max_explosion_depth: 3,
+            max_rerolls: 2,
+            ..Limits::default()
+        };
+        let dice = DiceGenerator::new().with_limits(limits);
+        // A constant die explodes forever
+        let dice_request = NumericRollRequest::new(2, NumericDice::ConstDice(6));
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        match rolls.explode(&dice, &[6]).unwrap_err().kind() {
+            ErrorKind::LimitExceeded(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+        let condition = RollCondition::Values(vec![6]);
+        match rolls.compound(&dice, &condition).unwrap_err().kind() {
+            ErrorKind::LimitExceeded(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+
+        let dice_request = NumericRollRequest::new(3, NumericDice::ConstDice(1));
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        match rolls.reroll(&dice, &[1]).unwrap_err().kind() {
+            ErrorKind::LimitExceeded(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+        assert!(rolls.reroll(&dice, &[2]).is_ok());
+    }
+
+    #[test]
+    fn transform_compound() {
+        let input = vec![1, 2, 3, 2, 1];
+        let dice_request =
+            NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
+        let dice = DiceGenerator::new();
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        // Each new roll is a 1
+        let condition = RollCondition::Threshold(Comparison::GreaterOrEqual, 2);
+        let output = rolls.compound(&dice, &condition).unwrap();
+        assert_eq!(output.rolls, vec![1, 3, 4, 3, 1]);
+        assert_eq!(output.history[2].values, vec![4]);
+        assert_eq!(output.history[2].compounded, vec![3, 1]);
+        assert_eq!(output.history[4].compounded, Vec::<NumericRoll>::new());
+
+        let output = rolls.penetrate(&dice, &condition).unwrap();
+        // The new dice are rolled together, so the penetrations also count 2s and 3s
+        assert_eq!(output.rolls, vec![1, 2, 3, 2, 1, 0, 1, 2, 0, 1, 0]);
+        assert_eq!(output.history[6].exploded_from, Some(2));
+    }
+
+    #[test]
+    fn die_history() {
+        let input = vec![1, 2, 3, 2, 1];
+        let dice_request =
+            NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
+        let dice = DiceGenerator::new();
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        assert_eq!(rolls.history.len(), 5);
+        assert!(rolls.history.iter().all(|die| die.values.len() == 1));
+
+        // Explosions add dice spawned by the exploding ones
+        let exploded = rolls.explode(&dice, &[2, 3]).unwrap();
+        assert_eq!(exploded.history.len(), exploded.rolls.len());
+        let parents: Vec<Option<usize>> = exploded
+            .history
+            .iter()
+            .map(|die| die.exploded_from)
+            .collect();
+        assert_eq!(
+            parents,
+            vec![
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(1),
+                Some(2),
+                Some(3),
+                Some(6),
+                Some(7),
+                Some(9)
+            ]
+        );
+
+        // Rerolled dice keep their previous values
+        let rerolled = rolls.reroll(&dice, &[1]).unwrap();
+        assert_eq!(rerolled.history[0].values, vec![1, 1]);
+        assert_eq!(rerolled.history[1].values, vec![2]);
+        assert_eq!(rerolled.history[4].values, vec![1, 1]);
+        let rerolled = rolls.reroll_worst(&dice, 1).unwrap();
+        assert_eq!(rerolled.history[0].values, vec![1, 1]);
+        assert_eq!(rerolled.history[4].values, vec![1]);
+
+        // Dropped dice are marked by the action, but stay in the history
+        let kept = rolls.keep_best(2).unwrap();
+        let dropped: Vec<Option<Action>> = kept
+            .history
+            .iter()
+            .map(|die| die.dropped_by.clone())
+            .collect();
+        assert_eq!(
+            dropped,
+            vec![
+                Some(Action::KeepBest(2)),
+                Some(Action::KeepBest(2)),
+                None,
+                None,
+                Some(Action::KeepBest(2))
+            ]
+        );
+        assert_eq!(kept.sum().history, kept.history);
+    }
+
     #[test]
     fn transform_keep_best() {
         let input = vec![1, 5, 3, 2, 5];
         let dice_request =
             NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
         let dice = DiceGenerator::new();
-        let rolls = NumericRolls::new(dice_request, &dice);
-        assert_eq!(rolls.keep_best(0).unwrap().rolls, vec![]);
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        assert_eq!(rolls.keep_best(0).unwrap().rolls, Vec::<NumericRoll>::new());
         assert_eq!(rolls.keep_best(1).unwrap().rolls, vec![5]);
         assert_eq!(rolls.keep_best(2).unwrap().rolls, vec![5, 5]);
         assert_eq!(rolls.keep_best(3).unwrap().rolls, vec![3, 5, 5]);
         assert_eq!(rolls.keep_best(4).unwrap().rolls, vec![2, 3, 5, 5]);
         assert_eq!(rolls.keep_best(5).unwrap().rolls, vec![1, 2, 3, 5, 5]);
-        assert!(!rolls.keep_best(8).is_ok());
+        assert!(rolls.keep_best(8).is_err());
     }
 
     #[test]
@@ -722,14 +2041,17 @@ mod tests {
         let dice_request =
             NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
         let dice = DiceGenerator::new();
-        let rolls = NumericRolls::new(dice_request, &dice);
-        assert_eq!(rolls.keep_worst(0).unwrap().rolls, vec![]);
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        assert_eq!(
+            rolls.keep_worst(0).unwrap().rolls,
+            Vec::<NumericRoll>::new()
+        );
         assert_eq!(rolls.keep_worst(1).unwrap().rolls, vec![1]);
         assert_eq!(rolls.keep_worst(2).unwrap().rolls, vec![1, 2]);
         assert_eq!(rolls.keep_worst(3).unwrap().rolls, vec![1, 2, 3]);
         assert_eq!(rolls.keep_worst(4).unwrap().rolls, vec![1, 2, 3, 5]);
         assert_eq!(rolls.keep_worst(5).unwrap().rolls, vec![1, 2, 3, 5, 5]);
-        assert!(!rolls.keep_worst(8).is_ok());
+        assert!(rolls.keep_worst(8).is_err());
     }
 
     #[test]
@@ -738,7 +2060,7 @@ mod tests {
         let dice_request =
             NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
         let dice = DiceGenerator::new();
-        let rolls = NumericRolls::new(dice_request, &dice);
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
         assert_eq!(
             rolls.reroll_best(&dice, 0).unwrap().rolls,
             vec![1, 2, 3, 4, 5]
@@ -763,7 +2085,7 @@ mod tests {
             rolls.reroll_best(&dice, 5).unwrap().rolls,
             vec![1, 2, 3, 4, 5]
         );
-        assert!(!rolls.keep_best(8).is_ok());
+        assert!(rolls.keep_best(8).is_err());
     }
 
     #[test]
@@ -772,7 +2094,7 @@ mod tests {
         let dice_request =
             NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
         let dice = DiceGenerator::new();
-        let rolls = NumericRolls::new(dice_request, &dice);
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
         assert_eq!(
             rolls.reroll_worst(&dice, 0).unwrap().rolls,
             vec![1, 2, 3, 4, 5]
@@ -797,7 +2119,101 @@ mod tests {
             rolls.reroll_worst(&dice, 5).unwrap().rolls,
             vec![5, 4, 3, 2, 1]
         );
-        assert!(!rolls.reroll_worst(&dice, 8).is_ok());
+        assert!(rolls.reroll_worst(&dice, 8).is_err());
+    }
+
+    #[test]
+    fn transform_count_successes() {
+        let input = vec![10, 8, 3, 1, 5, 9, 2, 7];
+        let dice_request =
+            NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
+        let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
+
+        let output = rolls.count_successes(Comparison::GreaterOrEqual, 8, &[1]);
+        assert_eq!(output.rolls, vec![2]);
+        assert_eq!(output.dice, NumericDice::AggregationResult);
+        assert_eq!(
+            output.description,
+            "8[10,8,3,1,5,9,2,7,...] Successes(>=8) Botch(1) (10 8 3 1 5 9 2 7 => 3 success(es), 5 failure(s), 1 botch(es))"
+        );
+        assert_eq!(
+            rolls
+                .count_successes(Comparison::LowerOrEqual, 2, &[])
+                .rolls,
+            vec![2]
+        );
+        assert_eq!(
+            rolls.count_successes(Comparison::Equal, 7, &[]).rolls,
+            vec![1]
+        );
+        // More botches than successes
+        assert_eq!(
+            rolls
+                .count_successes(Comparison::Equal, 7, &[1, 2, 3])
+                .rolls,
+            vec![-2]
+        );
+    }
+
+    #[test]
+    fn transform_bonus_penalty_dice() {
+        let dice = DiceGenerator::from_seed(5);
+        let rolls = NumericRolls::new(
+            NumericRollRequest::new(20, NumericDice::PercentileDice),
+            &dice,
+        )
+        .unwrap();
+        let bonus = rolls.bonus(&dice, 2).unwrap();
+        let penalty = rolls.penalty(&dice, 1).unwrap();
+        assert_eq!(bonus.description, "20D% Bonus(2)");
+        for (index, roll) in rolls.rolls.iter().enumerate() {
+            let units = percentile_digits(*roll).1;
+            // The roll is one of the candidates, and the units die is kept
+            let candidates = &bonus.history[index].values;
+            assert_eq!(candidates.len(), 3);
+            assert!(candidates.contains(roll));
+            assert_eq!(bonus.rolls[index], *candidates.iter().min().unwrap());
+            assert!(candidates
+                .iter()
+                .all(|candidate| percentile_digits(*candidate).1 == units));
+            let candidates = &penalty.history[index].values;
+            assert_eq!(candidates.len(), 2);
+            assert_eq!(penalty.rolls[index], *candidates.iter().max().unwrap());
+        }
+
+        let rolls = NumericRolls::new(
+            NumericRollRequest::new(2, NumericDice::NumberedDice(100)),
+            &dice,
+        )
+        .unwrap();
+        match rolls.bonus(&dice, 1).unwrap_err().kind() {
+            ErrorKind::IncompatibleAction(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn transform_skill_check() {
+        let input = vec![1, 9, 10, 25, 26, 50, 51, 96, 100];
+        let dice_request =
+            NumericRollRequest::new(input.len() as DiceNumber, NumericDice::RepeatingDice(input));
+        let rolls = NumericRolls::new(dice_request, &DiceGenerator::new()).unwrap();
+
+        let output = rolls.skill_check(50);
+        assert_eq!(output.rolls, vec![3, 3, 3, 2, 1, 1, 0, 0, 0]);
+        assert_eq!(output.dice, NumericDice::AggregationResult);
+        assert_eq!(
+            output.description,
+            "9[1,9,10,25,26,50,51,96,100,...] Skill(50) (1 9 10 25 26 50 51 96 100 => Extreme Extreme Extreme Hard Regular Regular Failure Failure Failure)"
+        );
+        assert_eq!(output.history[0].critical, Some(Critical::Success));
+        assert_eq!(output.history[7].critical, None);
+        assert_eq!(output.history[8].critical, Some(Critical::Failure));
+        // Under 50, the rolls from 96 are fumbles
+        assert_eq!(
+            rolls.skill_check(49).history[7].critical,
+            Some(Critical::Failure)
+        );
     }
 
     #[test]
@@ -807,20 +2223,38 @@ mod tests {
             .map(|i| {
                 let dice_request =
                     NumericRollRequest::new(1 as DiceNumber, NumericDice::ConstDice(i));
-                NumericRolls::new(dice_request, &dice)
+                NumericRolls::new(dice_request, &dice).unwrap()
             })
             .collect();
         let expected = NumericRolls {
             description: String::from(""),
             dice: NumericDice::AggregationResult,
+            id: None,
             rolls: vec![15],
+            history: vec![],
         };
-        let output = rolls.total();
+        let output = rolls.total().unwrap();
 
         assert_eq!(output.dice, expected.dice);
         assert_eq!(output.rolls[0], expected.rolls[0]);
     }
 
+    #[test]
+    fn transform_total_sum_overflow() {
+        let dice = DiceGenerator::new();
+        let rolls: Vec<NumericRolls> = vec![NumericDice::ConstDice(NumericRoll::MAX); 2]
+            .into_iter()
+            .map(|faces| NumericRolls::new(NumericRollRequest::new(1, faces), &dice).unwrap())
+            .collect();
+        assert_eq!(
+            rolls.total().unwrap_err(),
+            Error::bad_action_parameter("Arithmetic overflow")
+        );
+        assert!(NumericSession::from_str("100D2147483647 Total").is_err());
+        assert!(NumericSession::from_str("+2147483647 +1 Total").is_err());
+        assert!(NumericSession::from_str("2D{2147483647} Sum").is_err());
+    }
+
     #[test]
     fn aggregation_count_values() {
         let session = NumericSession::from_str(&String::from("+5 +10 +5 +10 +5 +22")).unwrap();
@@ -849,8 +2283,16 @@ mod tests {
 
     #[test]
     fn action_type_compatibilty() {
-        test_action_implemented_for_types(Action::RerollNumeric(vec![1]), true, false);
-        test_action_implemented_for_types(Action::RerollFudge(vec![FudgeRoll::Blank]), false, true);
+        test_action_implemented_for_types(
+            Action::RerollNumeric(RollCondition::Values(vec![1]), RerollMode::Once),
+            true,
+            false,
+        );
+        test_action_implemented_for_types(
+            Action::RerollFudge(vec![FudgeRoll::Blank], RerollMode::Until),
+            false,
+            true,
+        );
         test_action_implemented_for_types(Action::Sum, true, false);
         test_action_implemented_for_types(Action::MultiplyBy(42), true, false);
         test_action_implemented_for_types(Action::FlipFlop, true, false);
@@ -858,6 +2300,11 @@ mod tests {
         test_action_implemented_for_types(Action::KeepBest(1), true, false);
         println!("coucou2");
         test_action_implemented_for_types(Action::KeepWorst(1), true, false);
+        test_action_implemented_for_types(
+            Action::CountSuccesses(Comparison::GreaterOrEqual, 8, vec![1]),
+            true,
+            false,
+        );
     }
 
     /// Test the compatibility between actions and roll types
@@ -888,6 +2335,34 @@ mod tests {
         assert_eq!(test_text_types, rolls.is_ok());
     }
 
+    #[test]
+    fn aggregation_sum_by_id() {
+        let session =
+            NumericSession::from_str(&String::from("(FIRE +5) (FORCE +2) +1 (FIRE 2D1) +3"))
+                .unwrap();
+        let session = session.sum_by_id().unwrap();
+        let sums: Vec<(&str, NumericRoll)> = session
+            .rolls
+            .iter()
+            .map(|roll| (roll.description.as_str(), roll.rolls[0]))
+            .collect();
+        assert_eq!(
+            sums,
+            vec![
+                ("SUM(FIRE)", 7),
+                ("SUM(FORCE)", 2),
+                ("SUM(untagged)", 4),
+                ("TOTAL", 13)
+            ]
+        );
+        assert_eq!(session.rolls[0].id, Some(String::from("FIRE")));
+        assert_eq!(session.rolls[3].id, None);
+
+        let session =
+            NumericSession::from_str(&String::from("(FIRE +2147483647) (FIRE +1)")).unwrap();
+        assert!(session.sum_by_id().is_err());
+    }
+
     // TODO
     // #[test]
     // fn request_count_values() {
@@ -4,61 +4,319 @@ use crate::errors::Error;
 use core::fmt::Debug;
 use core::fmt::Display;
 use core::hash::Hash;
-use rand::rngs::ThreadRng;
-use rand::Rng;
-use std::cell::RefCell;
+use rand::rngs::StdRng;
+use rand::{FromEntropy, Rng, RngCore, SeedableRng};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 pub type DiceID = String;
 pub type DiceNumber = u8;
 /// Type of roll result for numbered dice (like D20)
-pub type NumericRoll = u32;
+pub type NumericRoll = i32;
 // Type of roll result for fudge dice (fate)
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub enum FudgeRoll {
     Plus,
     Minus,
     Blank,
 }
 
+impl FudgeRoll {
+    /// Numeric value of the roll: +1, 0 or -1.
+    pub fn value(self) -> i64 {
+        match self {
+            FudgeRoll::Plus => 1,
+            FudgeRoll::Blank => 0,
+            FudgeRoll::Minus => -1,
+        }
+    }
+}
+
+/// Symbol of the narrative dice (like the Genesys dice), cancelled by its opposite symbol.
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub enum Symbol {
+    Success,
+    Failure,
+    Advantage,
+    Threat,
+    /// Counts as a success too, but is never cancelled
+    Triumph,
+    /// Counts as a failure too, but is never cancelled
+    Despair,
+}
+
+impl Symbol {
+    /// Symbol cancelled by this one, if any.
+    pub fn opposite(self) -> Option<Symbol> {
+        match self {
+            Symbol::Success => Some(Symbol::Failure),
+            Symbol::Failure => Some(Symbol::Success),
+            Symbol::Advantage => Some(Symbol::Threat),
+            Symbol::Threat => Some(Symbol::Advantage),
+            Symbol::Triumph | Symbol::Despair => None,
+        }
+    }
+}
+
+/// Type of roll result for symbol dice: the face of the dice, with up to two symbols.
+#[derive(Debug, Default, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct SymbolRoll {
+    symbols: [Option<Symbol>; 2],
+}
+
+impl SymbolRoll {
+    /// Face without any symbol
+    pub const BLANK: SymbolRoll = SymbolRoll {
+        symbols: [None, None],
+    };
+
+    pub fn single(symbol: Symbol) -> SymbolRoll {
+        SymbolRoll {
+            symbols: [Some(symbol), None],
+        }
+    }
+
+    pub fn double(first: Symbol, second: Symbol) -> SymbolRoll {
+        SymbolRoll {
+            symbols: [Some(first), Some(second)],
+        }
+    }
+
+    /// Symbols of the face, in the order they were given.
+    pub fn symbols(&self) -> impl Iterator<Item = Symbol> + '_ {
+        self.symbols.iter().flatten().cloned()
+    }
+}
+
 pub trait RollBounds: Sized + Debug + Display + Clone + Copy + Hash + Eq {}
 impl RollBounds for NumericRoll {}
 impl RollBounds for FudgeRoll {}
+impl RollBounds for SymbolRoll {}
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub enum NumericDice {
     ConstDice(NumericRoll),
     NumberedDice(NumericRoll),
+    /// Dice rolling each of its faces with the same probability, like the average dice `{2,3,3,4,4,5}`
+    CustomDice(Vec<NumericRoll>),
+    /// Percentile dice `D%`, rolled as a tens die (00 to 90) and a units die (0 to 9), cf. [percentile_digits](fn.percentile_digits.html)
+    PercentileDice,
     RepeatingDice(Vec<NumericRoll>),
     AggregationResult,
 }
 
+/// Tens die (00 to 90) and units die (0 to 9) of a percentile roll, 100 being rolled as 00 and 0.
+///
+/// # Example
+/// ```
+/// # use letsroll::dice::{percentile_digits, percentile_roll};
+/// assert_eq!(percentile_digits(34), (30, 4));
+/// assert_eq!(percentile_digits(100), (0, 0));
+/// assert_eq!(percentile_roll(0, 0), 100);
+/// ```
+pub fn percentile_digits(roll: NumericRoll) -> (NumericRoll, NumericRoll) {
+    (roll % 100 / 10 * 10, roll % 10)
+}
+
+/// Percentile roll of a tens die and a units die (cf. [percentile_digits](fn.percentile_digits.html)).
+pub fn percentile_roll(tens: NumericRoll, units: NumericRoll) -> NumericRoll {
+    match tens + units {
+        0 => 100,
+        roll => roll,
+    }
+}
+
 impl NumericDice {
     pub fn get_max_value(&self) -> NumericRoll {
         match self {
             NumericDice::ConstDice(const_value) => *const_value,
             NumericDice::NumberedDice(sides) => *sides,
+            NumericDice::CustomDice(faces) => *faces.iter().max().unwrap_or(&0),
+            NumericDice::PercentileDice => 100,
             NumericDice::RepeatingDice(repeating_values) => {
                 *repeating_values.iter().max().unwrap_or(&0)
             }
             NumericDice::AggregationResult => unimplemented!(),
         }
     }
+
+    /// Lowest and highest natural rolls of the dice, if it is really rolled (constants are modifiers).
+    pub fn natural_range(&self) -> Option<(NumericRoll, NumericRoll)> {
+        match self {
+            NumericDice::NumberedDice(sides) => Some((1, *sides)),
+            NumericDice::CustomDice(faces) => Some((*faces.iter().min()?, *faces.iter().max()?)),
+            NumericDice::PercentileDice => Some((1, 100)),
+            NumericDice::RepeatingDice(repeating_values) => Some((
+                *repeating_values.iter().min()?,
+                *repeating_values.iter().max()?,
+            )),
+            NumericDice::ConstDice(_) | NumericDice::AggregationResult => None,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub enum FudgeDice {
     FudgeDice,
     ConstDice(FudgeRoll),
     RepeatingDice(Vec<FudgeRoll>),
 }
 
+/// Narrative dice of the Genesys system, whose faces are symbols instead of numbers.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub enum SymbolDice {
+    Boost,
+    Setback,
+    Ability,
+    Difficulty,
+    Proficiency,
+    Challenge,
+    ConstDice(SymbolRoll),
+    RepeatingDice(Vec<SymbolRoll>),
+}
+
+impl SymbolDice {
+    /// Faces of the dice, each one being equally likely.
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::dice::{Symbol, SymbolDice, SymbolRoll};
+    /// let faces = SymbolDice::Proficiency.faces();
+    /// assert_eq!(faces.len(), 12);
+    /// assert_eq!(faces.last(), Some(&SymbolRoll::single(Symbol::Triumph)));
+    /// ```
+    pub fn faces(&self) -> Vec<SymbolRoll> {
+        use Symbol::*;
+        let blank = SymbolRoll::BLANK;
+        let one = SymbolRoll::single;
+        let two = SymbolRoll::double;
+        match self {
+            SymbolDice::Boost => vec![
+                blank,
+                blank,
+                one(Success),
+                two(Success, Advantage),
+                two(Advantage, Advantage),
+                one(Advantage),
+            ],
+            SymbolDice::Setback => vec![
+                blank,
+                blank,
+                one(Failure),
+                one(Failure),
+                one(Threat),
+                one(Threat),
+            ],
+            SymbolDice::Ability => vec![
+                blank,
+                one(Success),
+                one(Success),
+                two(Success, Success),
+                one(Advantage),
+                one(Advantage),
+                two(Success, Advantage),
+                two(Advantage, Advantage),
+            ],
+            SymbolDice::Difficulty => vec![
+                blank,
+                one(Failure),
+                two(Failure, Failure),
+                one(Threat),
+                one(Threat),
+                one(Threat),
+                two(Threat, Threat),
+                two(Failure, Threat),
+            ],
+            SymbolDice::Proficiency => vec![
+                blank,
+                one(Success),
+                one(Success),
+                two(Success, Success),
+                two(Success, Success),
+                one(Advantage),
+                two(Success, Advantage),
+                two(Success, Advantage),
+                two(Success, Advantage),
+                two(Advantage, Advantage),
+                two(Advantage, Advantage),
+                one(Triumph),
+            ],
+            SymbolDice::Challenge => vec![
+                blank,
+                one(Failure),
+                one(Failure),
+                two(Failure, Failure),
+                two(Failure, Failure),
+                one(Threat),
+                one(Threat),
+                two(Failure, Threat),
+                two(Failure, Threat),
+                two(Threat, Threat),
+                two(Threat, Threat),
+                one(Despair),
+            ],
+            SymbolDice::ConstDice(face) => vec![*face],
+            SymbolDice::RepeatingDice(faces) => faces.clone(),
+        }
+    }
+}
+
 pub trait DiceBounds: Sized + Debug + Display + Clone {}
 impl DiceBounds for NumericDice {}
 impl DiceBounds for FudgeDice {}
+impl DiceBounds for SymbolDice {}
+
+/// Bounds on the amount of dice a request can roll, so that unreasonable requests
+/// (like exploding a die on its only value) fail instead of exhausting the memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct Limits {
+    /// Largest number of dice rolled at once
+    pub max_dice: usize,
+    /// Largest number of dice rolled for a whole request, rerolls and explosions included
+    pub max_total_dice: usize,
+    /// Largest number of successive explosions of a die
+    pub max_explosion_depth: usize,
+    /// Largest number of dice rerolled by a single action
+    pub max_rerolls: usize,
+}
 
-#[derive(Debug)]
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_dice: 100,
+            max_total_dice: 1000,
+            max_explosion_depth: 20,
+            max_rerolls: 100,
+        }
+    }
+}
+
+/// Source of randomness for all the rolls.
+///
+/// Clones of a generator share the same random source, so that several sessions
+/// can draw from one seeded sequence. They also share the count of dice rolled
+/// for the current request, checked against the generator [Limits](struct.Limits.html).
+///
+/// A generator can be sent to another thread, its clones included.
+#[derive(Clone)]
 pub struct DiceGenerator {
-    rng_ref: RefCell<ThreadRng>,
+    rng_ref: Arc<Mutex<Box<dyn RngCore + Send>>>,
+    limits: Limits,
+    rolled: Arc<AtomicUsize>,
+}
+
+impl fmt::Debug for DiceGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DiceGenerator")
+    }
 }
 
 pub trait Roll<T, V>
@@ -66,62 +324,205 @@ where
     T: RollBounds,
     V: DiceBounds,
 {
-    fn roll(&self, n: DiceNumber, dice: &V) -> Vec<T>;
+    /// Roll `n` dice, or fail if it would exceed the limits.
+    fn roll(&self, n: DiceNumber, dice: &V) -> Result<Vec<T>, Error>;
+
+    fn limits(&self) -> &Limits;
 }
 
 impl Roll<NumericRoll, NumericDice> for DiceGenerator {
-    fn roll(&self, n: DiceNumber, dice: &NumericDice) -> Vec<NumericRoll> {
-        match dice {
+    fn roll(&self, n: DiceNumber, dice: &NumericDice) -> Result<Vec<NumericRoll>, Error> {
+        self.count_rolled(n)?;
+        Ok(match dice {
             NumericDice::ConstDice(const_value) => self.roll_const_dice(n, const_value),
             NumericDice::NumberedDice(sides) => self.roll_numbered_dice(n, sides),
+            NumericDice::CustomDice(faces) if faces.is_empty() => {
+                return Err(Error::bad_action_parameter(
+                    "A dice needs at least one face",
+                ))
+            }
+            NumericDice::CustomDice(faces) => self.roll_custom_dice(n, faces),
+            NumericDice::PercentileDice => self.roll_percentile_dice(n),
             NumericDice::RepeatingDice(repeating_values) => {
                 self.roll_repeating(n, repeating_values)
             }
-            NumericDice::AggregationResult => unimplemented!(),
-        }
+            NumericDice::AggregationResult => {
+                return Err(Error::incompatible("Roll", "aggregated rolls"))
+            }
+        })
+    }
+
+    fn limits(&self) -> &Limits {
+        &self.limits
     }
 }
 
 impl Roll<FudgeRoll, FudgeDice> for DiceGenerator {
-    fn roll(&self, n: DiceNumber, dice: &FudgeDice) -> Vec<FudgeRoll> {
-        match dice {
+    fn roll(&self, n: DiceNumber, dice: &FudgeDice) -> Result<Vec<FudgeRoll>, Error> {
+        self.count_rolled(n)?;
+        Ok(match dice {
             FudgeDice::ConstDice(const_value) => self.roll_const_dice(n, const_value),
             FudgeDice::FudgeDice => self.roll_fudge_dice(n),
             FudgeDice::RepeatingDice(repeating_values) => self.roll_repeating(n, repeating_values),
-        }
+        })
+    }
+
+    fn limits(&self) -> &Limits {
+        &self.limits
+    }
+}
+
+impl Roll<SymbolRoll, SymbolDice> for DiceGenerator {
+    fn roll(&self, n: DiceNumber, dice: &SymbolDice) -> Result<Vec<SymbolRoll>, Error> {
+        self.count_rolled(n)?;
+        Ok(match dice {
+            SymbolDice::ConstDice(const_value) => self.roll_const_dice(n, const_value),
+            SymbolDice::RepeatingDice(repeating_values) => self.roll_repeating(n, repeating_values),
+            _ => self.roll_custom_dice(n, &dice.faces()),
+        })
+    }
+
+    fn limits(&self) -> &Limits {
+        &self.limits
+    }
+}
+
+impl Default for DiceGenerator {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl DiceGenerator {
     pub fn new() -> DiceGenerator {
+        DiceGenerator::from_rng(StdRng::from_entropy())
+    }
+
+    /// Create a generator whose rolls can be reproduced by using the same seed.
+    /// # Example
+    /// ```
+    /// # use letsroll::dice::{DiceGenerator, NumericDice, Roll, NumericRoll};
+    /// let first: Vec<NumericRoll> = DiceGenerator::from_seed(42).roll(10, &NumericDice::NumberedDice(20)).unwrap();
+    /// let second: Vec<NumericRoll> = DiceGenerator::from_seed(42).roll(10, &NumericDice::NumberedDice(20)).unwrap();
+    /// assert_eq!(first, second);
+    /// ```
+    pub fn from_seed(seed: u64) -> DiceGenerator {
+        DiceGenerator::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    /// Create a generator drawing from any caller-supplied random source.
+    pub fn from_rng<R: RngCore + Send + 'static>(rng: R) -> DiceGenerator {
         DiceGenerator {
-            rng_ref: RefCell::new(rand::thread_rng()),
+            rng_ref: Arc::new(Mutex::new(Box::new(rng))),
+            limits: Limits::default(),
+            rolled: Arc::new(AtomicUsize::new(0)),
         }
     }
 
-    pub fn roll_repeating<T: RollBounds>(
-        &self,
-        n: DiceNumber,
-        repeating_values: &Vec<T>,
-    ) -> Vec<T> {
-        let mut repeat_values = repeating_values.clone();
+    /// Random source shared by the clones of the generator.
+    fn rng(&self) -> MutexGuard<'_, Box<dyn RngCore + Send>> {
+        // A panic while rolling leaves the random source usable
+        self.rng_ref
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Replace the default limits of the generator.
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::dice::{DiceGenerator, Limits, NumericDice, NumericRoll, Roll};
+    /// # use letsroll::errors::ErrorKind;
+    /// let limits = Limits { max_dice: 3, ..Limits::default() };
+    /// let dice = DiceGenerator::from_seed(42).with_limits(limits);
+    /// let rolls: Result<Vec<NumericRoll>, _> = dice.roll(4, &NumericDice::NumberedDice(6));
+    /// match rolls.unwrap_err().kind() {
+    ///     ErrorKind::LimitExceeded(_) => (),
+    ///     kind => panic!("Unexpected error {:?}", kind),
+    /// }
+    /// ```
+    pub fn with_limits(mut self, limits: Limits) -> DiceGenerator {
+        self.limits = limits;
+        self
+    }
+
+    /// Start counting the dice rolled for a new request.
+    pub(crate) fn reset_rolled(&self) {
+        self.rolled.store(0, Ordering::SeqCst);
+    }
+
+    fn count_rolled(&self, n: DiceNumber) -> Result<(), Error> {
+        if n as usize > self.limits.max_dice {
+            return Err(Error::limit_exceeded(
+                "dice rolled at once",
+                self.limits.max_dice,
+            ));
+        }
+        let rolled = self.rolled.load(Ordering::SeqCst) + n as usize;
+        if rolled > self.limits.max_total_dice {
+            return Err(Error::limit_exceeded(
+                "dice rolled for the request",
+                self.limits.max_total_dice,
+            ));
+        }
+        self.rolled.store(rolled, Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub fn roll_repeating<T: RollBounds>(&self, n: DiceNumber, repeating_values: &[T]) -> Vec<T> {
+        let mut repeat_values = repeating_values.to_vec();
         for _ in 0..(n as usize / repeating_values.len()) {
-            repeat_values.append(&mut repeating_values.clone());
+            repeat_values.extend_from_slice(repeating_values);
         }
         repeat_values[0..(n as usize)].to_vec()
     }
 
     pub fn roll_const_dice<T: RollBounds>(&self, n: DiceNumber, const_value: &T) -> Vec<T> {
-        (1..n + 1).map(|_| const_value.clone()).collect()
+        (1..n + 1).map(|_| *const_value).collect()
     }
 
     pub fn roll_numbered_dice(&self, n: DiceNumber, sides: &NumericRoll) -> Vec<NumericRoll> {
-        let mut rng = self.rng_ref.borrow_mut();
-        (1..n + 1).map(|_| rng.gen_range(1, sides + 1)).collect()
+        let mut rng = self.rng();
+        // Shifted after the draw, so that the largest sides don't overflow
+        (1..n + 1).map(|_| rng.gen_range(0, *sides) + 1).collect()
+    }
+
+    /// Roll `n` dice with the given faces, each face being equally likely.
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::dice::DiceGenerator;
+    /// let rolls = DiceGenerator::from_seed(42).roll_custom_dice(100, &[0, 1, 1, 2]);
+    /// assert!(rolls.iter().all(|roll| [0, 1, 2].contains(roll)));
+    /// assert!(rolls.contains(&0) && rolls.contains(&2));
+    /// ```
+    pub fn roll_custom_dice<T: RollBounds>(&self, n: DiceNumber, faces: &[T]) -> Vec<T> {
+        let mut rng = self.rng();
+        (1..n + 1)
+            .map(|_| faces[rng.gen_range(0, faces.len())])
+            .collect()
+    }
+
+    /// Roll `n` percentile dice, each one as a tens die and a units die.
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::dice::DiceGenerator;
+    /// let rolls = DiceGenerator::from_seed(42).roll_percentile_dice(100);
+    /// assert!(rolls.iter().all(|roll| (1..=100).contains(roll)));
+    /// ```
+    pub fn roll_percentile_dice(&self, n: DiceNumber) -> Vec<NumericRoll> {
+        let mut rng = self.rng();
+        (1..n + 1)
+            .map(|_| {
+                let tens = rng.gen_range(0, 10) * 10;
+                percentile_roll(tens, rng.gen_range(0, 10))
+            })
+            .collect()
     }
 
     pub fn roll_fudge_dice(&self, n: DiceNumber) -> Vec<FudgeRoll> {
-        let mut rng = self.rng_ref.borrow_mut();
+        let mut rng = self.rng();
         (1..n + 1)
             .map(|_| match rng.gen_range(1, 4) {
                 1 => FudgeRoll::Blank,
@@ -133,6 +534,7 @@ impl DiceGenerator {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct RollRequest<T: DiceBounds> {
     pub(crate) number: DiceNumber,
     pub(crate) id: Option<DiceID>,
@@ -142,6 +544,7 @@ pub struct RollRequest<T: DiceBounds> {
 
 pub type NumericRollRequest = RollRequest<NumericDice>;
 pub type FudgeRollRequest = RollRequest<FudgeDice>;
+pub type SymbolRollRequest = RollRequest<SymbolDice>;
 
 impl<T: DiceBounds> RollRequest<T> {
     pub fn new(number: DiceNumber, dice: T) -> RollRequest<T> {
@@ -163,6 +566,19 @@ impl<T: DiceBounds> RollRequest<T> {
         self
     }
 
+    /// `true` if the dice of the request are doubled on a critical success (cf. [Action::CriticalDamage](../actions/enum.Action.html#variant.CriticalDamage)).
+    pub fn deals_critical_damage(&self) -> bool {
+        self.actions.contains(&Action::CriticalDamage)
+    }
+
+    /// The same request with twice the dice, for a critical success: the constant modifiers are separate requests.
+    pub(crate) fn with_critical_damage(&self) -> RollRequest<T> {
+        RollRequest {
+            number: self.number.saturating_mul(2),
+            ..self.clone()
+        }
+    }
+
     pub fn add_actions(self, actions: Vec<Action>) -> RollRequest<T> {
         let mut self_mut = self;
         for action in actions.into_iter() {
@@ -173,37 +589,229 @@ impl<T: DiceBounds> RollRequest<T> {
 }
 
 impl<V: DiceBounds> RollRequest<V> {
-    pub fn roll<T: RollBounds>(&self, dice: &Roll<T, V>) -> Result<Rolls<T, V>, Error>
+    pub fn roll<T: RollBounds>(&self, dice: &dyn Roll<T, V>) -> Result<Rolls<T, V>, Error>
+    where
+        Rolls<T, V>: Apply<T, V>,
+    {
+        self.apply_actions(Rolls::<T, V>::new(self.clone(), dice)?, dice)
+    }
+
+    /// Apply the actions of the request to its rolls.
+    pub fn apply_actions<T: RollBounds>(
+        &self,
+        rolls: Rolls<T, V>,
+        dice: &dyn Roll<T, V>,
+    ) -> Result<Rolls<T, V>, Error>
+    where
+        Rolls<T, V>: Apply<T, V>,
+    {
+        self.apply_first_actions(rolls, self.actions.len(), dice)
+    }
+
+    /// Apply the `count` first actions of the request to its rolls.
+    ///
+    /// An action rolling the group twice (like `Advantage`) rolls the dice of the request again,
+    /// and applies the previous actions to these new rolls too.
+    fn apply_first_actions<T: RollBounds>(
+        &self,
+        rolls: Rolls<T, V>,
+        count: usize,
+        dice: &dyn Roll<T, V>,
+    ) -> Result<Rolls<T, V>, Error>
     where
         Rolls<T, V>: Apply<T, V>,
     {
-        let mut rolls = Rolls::<T, V>::new(self.clone(), dice);
-        for action in self.actions.iter() {
-            rolls = Apply::<T, V>::apply(&rolls, action, dice)?;
+        let mut rolls = rolls;
+        for (index, action) in self.actions[..count].iter().enumerate() {
+            rolls = if action.rolls_group_twice() {
+                let other =
+                    self.apply_first_actions(Rolls::new(self.clone(), dice)?, index, dice)?;
+                rolls.apply_twice(action, &other)?
+            } else {
+                Apply::<T, V>::apply(&rolls, action, dice)?
+            };
         }
         Ok(rolls)
     }
 }
 
-#[derive(Debug)]
+/// What happened to one rolled die while the actions were applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct DieHistory<T: RollBounds> {
+    /// Successive values of the die: the last one is the current value, the previous ones were rerolled
+    /// (or replaced by the tens of [bonus and penalty dice](../actions/trait.BonusDice.html)).
+    pub values: Vec<T>,
+    /// Action that discarded the die, if any.
+    pub dropped_by: Option<Action>,
+    /// Index (in the history) of the die whose explosion added this one.
+    pub exploded_from: Option<usize>,
+    /// Rolls added together into the current value by a compounding explosion.
+    pub compounded: Vec<T>,
+    /// Critical status of the natural roll, if it was checked by the [Critical](../actions/enum.Action.html#variant.Critical) action.
+    pub critical: Option<Critical>,
+}
+
+/// Natural roll flagged by the [Critical](../actions/enum.Action.html#variant.Critical) action,
+/// like a natural 20 or a natural 1 on a D20.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub enum Critical {
+    Success,
+    Failure,
+}
+
+impl Critical {
+    /// Critical status of several rolls: a critical success wins over a critical failure.
+    pub(crate) fn combine<I: IntoIterator<Item = Critical>>(statuses: I) -> Option<Critical> {
+        statuses
+            .into_iter()
+            .fold(None, |combined, status| match (combined, status) {
+                (Some(Critical::Success), _) | (_, Critical::Success) => Some(Critical::Success),
+                _ => Some(Critical::Failure),
+            })
+    }
+}
+
+impl<T: RollBounds> DieHistory<T> {
+    pub fn new(value: T) -> DieHistory<T> {
+        DieHistory {
+            values: vec![value],
+            dropped_by: None,
+            exploded_from: None,
+            compounded: vec![],
+            critical: None,
+        }
+    }
+
+    /// Current value of the die.
+    pub fn value(&self) -> T {
+        *self.values.last().expect("A die always has a value")
+    }
+
+    /// `true` if the die still counts in the rolls.
+    pub fn is_kept(&self) -> bool {
+        self.dropped_by.is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct Rolls<T: RollBounds, V: DiceBounds> {
     pub dice: V,
+    pub id: Option<DiceID>,
     pub description: String,
     pub rolls: Vec<T>,
+    /// Audit trail of each die rolled, in the order they were rolled (empty for aggregated results).
+    pub history: Vec<DieHistory<T>>,
 }
 
-impl<T: RollBounds, V: DiceBounds> Rolls<T, V> {
-    pub fn new(dice_request: RollRequest<V>, dice: &Roll<T, V>) -> Rolls<T, V> {
+/// Numeric value of fudge rolls (+1, 0 or -1 per die), to add them to numeric rolls.
+///
+/// # Example
+/// ```
+/// # use letsroll::dice::{DiceGenerator, FudgeDice, FudgeRoll, FudgeRolls, FudgeRollRequest, NumericRolls};
+/// let faces = vec![FudgeRoll::Plus, FudgeRoll::Blank, FudgeRoll::Minus, FudgeRoll::Plus];
+/// let request = FudgeRollRequest::new(4, FudgeDice::RepeatingDice(faces));
+/// let rolls = FudgeRolls::new(request, &DiceGenerator::new()).unwrap();
+/// let numeric = NumericRolls::from(&rolls);
+/// assert_eq!(numeric.rolls, vec![1, 0, -1, 1]);
+/// assert_eq!(numeric.description, "4[+,0,-,+,...] = +0-+");
+/// ```
+impl From<&FudgeRolls> for NumericRolls {
+    fn from(rolls: &FudgeRolls) -> NumericRolls {
+        let value = |roll: &FudgeRoll| roll.value() as NumericRoll;
         Rolls {
+            description: format!(
+                "{} = {}",
+                rolls.description,
+                rolls
+                    .rolls
+                    .iter()
+                    .map(|roll| roll.to_string())
+                    .collect::<String>()
+            ),
+            dice: NumericDice::AggregationResult,
+            id: rolls.id.clone(),
+            rolls: rolls.rolls.iter().map(value).collect(),
+            history: rolls
+                .history
+                .iter()
+                .map(|die| DieHistory {
+                    values: die.values.iter().map(value).collect(),
+                    dropped_by: die.dropped_by.clone(),
+                    exploded_from: die.exploded_from,
+                    compounded: die.compounded.iter().map(value).collect(),
+                    critical: die.critical,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<T: RollBounds, V: DiceBounds> Rolls<T, V> {
+    pub fn new(dice_request: RollRequest<V>, dice: &dyn Roll<T, V>) -> Result<Rolls<T, V>, Error> {
+        let rolls = dice.roll(dice_request.number, &dice_request.dice)?;
+        Ok(Rolls {
             description: dice_request.to_string(),
-            rolls: dice.roll(dice_request.number, &dice_request.dice),
+            history: rolls.iter().map(|roll| DieHistory::new(*roll)).collect(),
+            rolls,
             dice: dice_request.dice,
+            id: dice_request.id,
+        })
+    }
+
+    /// Critical status of the kept dice: a critical success wins over a critical failure.
+    pub fn critical(&self) -> Option<Critical> {
+        Critical::combine(
+            self.history
+                .iter()
+                .filter(|die| die.is_kept())
+                .filter_map(|die| die.critical),
+        )
+    }
+
+    /// Index in the history of the kept die showing `value`, skipping the dice already picked.
+    ///
+    /// Actions like [KeepBest](../actions/trait.KeepBest.html) sort the rolls, so the dice are found by value.
+    pub(crate) fn find_die(&self, value: T, picked: &[usize]) -> Option<usize> {
+        self.history
+            .iter()
+            .enumerate()
+            .find(|(index, die)| die.is_kept() && die.value() == value && !picked.contains(index))
+            .map(|(index, _)| index)
+    }
+
+    /// History after the dice showing the `old` values were rerolled into the `new` values.
+    pub(crate) fn history_after_reroll(&self, old: &[T], new: &[T]) -> Vec<DieHistory<T>> {
+        let mut history = self.history.clone();
+        let mut picked: Vec<usize> = vec![];
+        for (old_roll, new_roll) in old.iter().zip(new) {
+            if let Some(index) = self.find_die(*old_roll, &picked) {
+                history[index].values.push(*new_roll);
+                picked.push(index);
+            }
+        }
+        history
+    }
+
+    /// History after the dice showing the `dropped` values were discarded by `action`.
+    pub(crate) fn history_after_drop(&self, dropped: &[T], action: Action) -> Vec<DieHistory<T>> {
+        let mut history = self.history.clone();
+        let mut picked: Vec<usize> = vec![];
+        for roll in dropped.iter() {
+            if let Some(index) = self.find_die(*roll, &picked) {
+                history[index].dropped_by = Some(action.clone());
+                picked.push(index);
+            }
         }
+        history
     }
 }
 
 pub type NumericRolls = Rolls<NumericRoll, NumericDice>;
 pub type FudgeRolls = Rolls<FudgeRoll, FudgeDice>;
+pub type SymbolRolls = Rolls<SymbolRoll, SymbolDice>;
 
 #[cfg(test)]
 mod tests {
@@ -214,7 +822,9 @@ mod tests {
         let dice = DiceGenerator::new();
         let const_value = 42;
         let roll_number = 5;
-        let rolls = dice.roll(roll_number, &NumericDice::ConstDice(const_value));
+        let rolls = dice
+            .roll(roll_number, &NumericDice::ConstDice(const_value))
+            .unwrap();
         assert_eq!(rolls.len(), roll_number as usize);
         for roll in rolls.iter() {
             assert_eq!(*roll, const_value);
@@ -222,7 +832,9 @@ mod tests {
 
         let const_value = FudgeRoll::Blank;
         let roll_number = 2;
-        let rolls = dice.roll(roll_number, &FudgeDice::ConstDice(const_value));
+        let rolls = dice
+            .roll(roll_number, &FudgeDice::ConstDice(const_value))
+            .unwrap();
         assert_eq!(rolls.len(), roll_number as usize);
         for roll in rolls.iter() {
             assert_eq!(*roll, const_value);
@@ -234,7 +846,9 @@ mod tests {
         let dice = DiceGenerator::new();
         let dice_sides = 42;
         let roll_number = 5;
-        let rolls = dice.roll(roll_number, &NumericDice::NumberedDice(dice_sides));
+        let rolls = dice
+            .roll(roll_number, &NumericDice::NumberedDice(dice_sides))
+            .unwrap();
         assert_eq!(rolls.len(), roll_number as usize);
         for roll in rolls.iter() {
             assert!(*roll > 0, "Numbered dice generator rolls should be > 0");
@@ -245,25 +859,118 @@ mod tests {
         }
     }
 
+    #[test]
+    fn percentile_dice_generation() {
+        let dice = DiceGenerator::from_seed(100);
+        let mut counts = [0; 100];
+        for _ in 0..100 {
+            for roll in dice.roll_percentile_dice(200) {
+                counts[roll as usize - 1] += 1;
+            }
+        }
+        // Each value from 1 to 100 is rolled about 200 times
+        for (value, count) in counts.iter().enumerate() {
+            assert!(
+                (120..280).contains(count),
+                "{} rolled {} times",
+                value + 1,
+                count
+            );
+        }
+    }
+
+    #[test]
+    fn seeded_generation() {
+        let dice = DiceGenerator::from_seed(1234);
+        let other_dice = DiceGenerator::from_seed(1234);
+        let numeric_rolls: Vec<NumericRoll> =
+            dice.roll(20, &NumericDice::NumberedDice(100)).unwrap();
+        let fudge_rolls: Vec<FudgeRoll> = dice.roll(20, &FudgeDice::FudgeDice).unwrap();
+        assert_eq!(
+            numeric_rolls,
+            other_dice
+                .roll(20, &NumericDice::NumberedDice(100))
+                .unwrap()
+        );
+        assert_eq!(
+            fudge_rolls,
+            other_dice.roll(20, &FudgeDice::FudgeDice).unwrap()
+        );
+
+        // Clones share the same random sequence
+        let dice = DiceGenerator::from_seed(1234);
+        let cloned_dice = dice.clone();
+        let first: Vec<NumericRoll> = dice.roll(10, &NumericDice::NumberedDice(100)).unwrap();
+        let second: Vec<NumericRoll> = cloned_dice
+            .roll(10, &NumericDice::NumberedDice(100))
+            .unwrap();
+        assert_eq!(first, numeric_rolls[0..10].to_vec());
+        assert_eq!(second, numeric_rolls[10..20].to_vec());
+    }
+
+    #[test]
+    fn roll_limits() {
+        let limits = Limits {
+            max_dice: 5,
+            max_total_dice: 8,
+            ..Limits::default()
+        };
+        let dice = DiceGenerator::from_seed(1234).with_limits(limits);
+        assert_eq!(*Roll::<NumericRoll, NumericDice>::limits(&dice), limits);
+        let rolls: Result<Vec<NumericRoll>, Error> = dice.roll(6, &NumericDice::NumberedDice(6));
+        assert!(rolls.is_err());
+
+        // The dice rolled are counted until the next request, clones included
+        let rolls: Vec<NumericRoll> = dice.roll(5, &NumericDice::NumberedDice(6)).unwrap();
+        assert_eq!(rolls.len(), 5);
+        let rolls: Result<Vec<FudgeRoll>, Error> = dice.clone().roll(4, &FudgeDice::FudgeDice);
+        match rolls.unwrap_err().kind() {
+            crate::errors::ErrorKind::LimitExceeded(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+        dice.reset_rolled();
+        let rolls: Vec<FudgeRoll> = dice.roll(4, &FudgeDice::FudgeDice).unwrap();
+        assert_eq!(rolls.len(), 4);
+    }
+
+    #[test]
+    fn dice_sent_to_another_thread() {
+        let dice = DiceGenerator::from_seed(42);
+        let expected: Vec<NumericRoll> = DiceGenerator::from_seed(42)
+            .roll(10, &NumericDice::NumberedDice(20))
+            .unwrap();
+        let rolls = std::thread::spawn(move || {
+            let rolls: Vec<NumericRoll> = dice.roll(10, &NumericDice::NumberedDice(20)).unwrap();
+            rolls
+        })
+        .join()
+        .unwrap();
+        assert_eq!(rolls, expected);
+    }
+
     #[test]
     fn repeating_dice() {
         let dice = DiceGenerator::new();
         let repeating_values = vec![1, 2, 3, 4, 5];
 
         assert_eq!(
-            dice.roll(0, &NumericDice::RepeatingDice(repeating_values.clone())),
-            vec![]
+            dice.roll(0, &NumericDice::RepeatingDice(repeating_values.clone()))
+                .unwrap(),
+            Vec::<NumericRoll>::new()
         );
         assert_eq!(
-            dice.roll(3, &NumericDice::RepeatingDice(repeating_values.clone())),
+            dice.roll(3, &NumericDice::RepeatingDice(repeating_values.clone()))
+                .unwrap(),
             vec![1, 2, 3]
         );
         assert_eq!(
-            dice.roll(5, &NumericDice::RepeatingDice(repeating_values.clone())),
+            dice.roll(5, &NumericDice::RepeatingDice(repeating_values.clone()))
+                .unwrap(),
             vec![1, 2, 3, 4, 5]
         );
         assert_eq!(
-            dice.roll(15, &NumericDice::RepeatingDice(repeating_values.clone())),
+            dice.roll(15, &NumericDice::RepeatingDice(repeating_values.clone()))
+                .unwrap(),
             vec![1, 2, 3, 4, 5, 1, 2, 3, 4, 5, 1, 2, 3, 4, 5]
         );
     }
@@ -23,13 +23,39 @@ impl Error {
         }
     }
 
+    /// Error on a request that doesn't follow the grammar, with the keyword that was probably meant.
+    pub(crate) fn syntax<R: pest::RuleType>(
+        err: pest::error::Error<R>,
+        suggestion: Option<String>,
+    ) -> Error {
+        let (start, end) = match err.line_col {
+            pest::error::LineColLocation::Pos(start) => (start, None),
+            pest::error::LineColLocation::Span(start, end) => (start, Some(end)),
+        };
+        let expected = match &err.variant {
+            pest::error::ErrorVariant::ParsingError { positives, .. } => {
+                positives.iter().map(|rule| format!("{:?}", rule)).collect()
+            }
+            pest::error::ErrorVariant::CustomError { .. } => vec![],
+        };
+        Error {
+            kind: ErrorKind::Syntax(SyntaxError {
+                start,
+                end,
+                expected,
+                suggestion,
+                message: err.to_string(),
+            }),
+        }
+    }
+
     pub(crate) fn file<E: error::Error>(err: E) -> Error {
         Error {
             kind: ErrorKind::File(err.to_string()),
         }
     }
 
-    pub(crate) fn incompatible(action: &String, roll_type: &String) -> Error {
+    pub(crate) fn incompatible(action: &str, roll_type: &str) -> Error {
         Error {
             kind: ErrorKind::IncompatibleAction(format!(
                 "Action {:?} not supported by roll type {:?}",
@@ -38,9 +64,45 @@ impl Error {
         }
     }
 
-    pub(crate) fn bad_action_parameter(message: &String) -> Error {
+    pub(crate) fn bad_action_parameter(message: &str) -> Error {
         Error {
-            kind: ErrorKind::BadActionParameter(message.clone()),
+            kind: ErrorKind::BadActionParameter(message.to_string()),
+        }
+    }
+
+    pub(crate) fn not_computable(message: &str) -> Error {
+        Error {
+            kind: ErrorKind::NotComputable(format!(
+                "The probability distribution of {} can't be computed exactly",
+                message
+            )),
+        }
+    }
+
+    pub(crate) fn not_writable(message: &str) -> Error {
+        Error {
+            kind: ErrorKind::NotWritable(format!("{} can't be written as a request", message)),
+        }
+    }
+
+    pub(crate) fn undefined_name(name: &str) -> Error {
+        Error {
+            kind: ErrorKind::UndefinedName(format!("{} is not defined", name)),
+        }
+    }
+
+    pub(crate) fn recursive_definition(names: &[String]) -> Error {
+        Error {
+            kind: ErrorKind::RecursiveDefinition(format!(
+                "{} is defined with itself",
+                names.join(" -> ")
+            )),
+        }
+    }
+
+    pub(crate) fn limit_exceeded(what: &str, limit: usize) -> Error {
+        Error {
+            kind: ErrorKind::LimitExceeded(format!("More than {} {}", limit, what)),
         }
     }
 
@@ -64,7 +126,32 @@ impl From<std::io::Error> for Error {
 
 impl<R: pest::RuleType> From<pest::error::Error<R>> for Error {
     fn from(error: pest::error::Error<R>) -> Self {
-        Error::parse(error)
+        Error::syntax(error, None)
+    }
+}
+
+/// Details of a request that doesn't follow the request syntax.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxError {
+    /// Line and column (starting at 1) where the error starts.
+    pub start: (usize, usize),
+    /// Line and column where the error ends, if it is about a part of the request.
+    pub end: Option<(usize, usize)>,
+    /// Names of the grammar rules that were expected at the error position.
+    pub expected: Vec<String>,
+    /// Keyword that was probably meant, for a misspelled one.
+    pub suggestion: Option<String>,
+    /// Description of the error, showing where it is in the request.
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n  = did you mean {}?", suggestion)?;
+        }
+        Ok(())
     }
 }
 
@@ -77,6 +164,10 @@ pub enum ErrorKind {
     /// The string here is the underlying error converted to a string.
     Parse(String),
 
+    /// An error that occurred because a request doesn't follow the request syntax,
+    /// with the position of the error.
+    Syntax(SyntaxError),
+
     /// An error that occurred as a result of parsing a dice request.
     ///
     /// The string here is a detailed explanation of what caused the parsing to fail.
@@ -93,17 +184,38 @@ pub enum ErrorKind {
 
     // Occurs when an action parameter is invalid
     BadActionParameter(String),
+
+    // Occurs when the probability distribution of a request can't be computed exactly
+    NotComputable(String),
+
+    // Occurs when a request structure has no equivalent in the request syntax
+    NotWritable(String),
+
+    // Occurs when a request uses a variable that is not defined
+    UndefinedName(String),
+
+    // Occurs when a variable or macro is defined with itself, directly or not
+    RecursiveDefinition(String),
+
+    // Occurs when a request would roll more dice than the configured limits allow
+    LimitExceeded(String),
 }
 
 impl error::Error for Error {
     fn description(&self) -> &str {
         match self.kind {
             ErrorKind::Parse(_) => "Request parsing error",
+            ErrorKind::Syntax(_) => "Request syntax error",
             ErrorKind::ParseDice(_) => "Dice parsing error",
             ErrorKind::IncompatibleAction(_) => "Action applying error",
             ErrorKind::BadDice(_) => "Dice creation error",
             ErrorKind::File(_) => "File operation error",
             ErrorKind::BadActionParameter(_) => "Bad action parameter error",
+            ErrorKind::NotComputable(_) => "Probability computation error",
+            ErrorKind::NotWritable(_) => "Request writing error",
+            ErrorKind::UndefinedName(_) => "Undefined name error",
+            ErrorKind::RecursiveDefinition(_) => "Recursive definition error",
+            ErrorKind::LimitExceeded(_) => "Limit exceeded error",
         }
     }
 }
@@ -112,11 +224,19 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.kind {
             ErrorKind::Parse(ref s) => write!(f, "Request parse error: {}", s),
+            ErrorKind::Syntax(ref e) => write!(f, "Request syntax error: {}", e),
             ErrorKind::ParseDice(ref s) => write!(f, "Dice parsing error: {}", s),
             ErrorKind::IncompatibleAction(ref s) => write!(f, "Action applying error: {}", s),
             ErrorKind::BadDice(ref s) => write!(f, "Dice creation error: {}", s),
             ErrorKind::File(ref s) => write!(f, "File operation error: {}", s),
             ErrorKind::BadActionParameter(ref s) => write!(f, "Bad action parameter error {}", s),
+            ErrorKind::NotComputable(ref s) => write!(f, "Probability computation error: {}", s),
+            ErrorKind::NotWritable(ref s) => write!(f, "Request writing error: {}", s),
+            ErrorKind::UndefinedName(ref s) => write!(f, "Undefined name error: {}", s),
+            ErrorKind::RecursiveDefinition(ref s) => {
+                write!(f, "Recursive definition error: {}", s)
+            }
+            ErrorKind::LimitExceeded(ref s) => write!(f, "Limit exceeded error: {}", s),
         }
     }
 }
@@ -1,2 +1,3 @@
 pub mod read;
-pub mod write;
\ No newline at end of file
+pub mod repl;
+pub mod write;
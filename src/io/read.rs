@@ -1,24 +1,124 @@
 use crate::actions::Action;
-use crate::actions::Aggregation;
+use crate::actions::{Aggregation, Comparison, RerollMode, RollCondition};
 use crate::dice::*;
 use crate::errors::{Error, ErrorKind};
-use crate::MultiTypeSession;
-use crate::{AggregatableSession, FudgeSession, NumericSession, TransformableSession};
+use crate::{Expression, MultiTypeRequest, MultiTypeSession, Operator, Rounding};
+use crate::{FudgeSession, NumericSession};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use pest::error::{ErrorVariant, InputLocation};
+use pest::iterators::Pairs;
 use pest::Parser;
 
 #[derive(Parser)]
 #[grammar = "roll_request.pest"]
 pub struct RequestParser;
 
+/// Keywords of the request syntax, suggested for the misspelled words.
+const KEYWORDS: [&str; 31] = [
+    "Sum",
+    "Flip",
+    "Total",
+    "Concat",
+    "KeepBest",
+    "KeepWorst",
+    "RerollBest",
+    "RerollWorst",
+    "RerollUntil",
+    "Reroll",
+    "Explode",
+    "Compound",
+    "Penetrate",
+    "Successes",
+    "Botch",
+    "Advantage",
+    "Disadvantage",
+    "Crit",
+    "CritDamage",
+    "Bonus",
+    "Penalty",
+    "Skill",
+    "Count",
+    "SumById",
+    "Cancel",
+    "Boost",
+    "Setback",
+    "Ability",
+    "Difficulty",
+    "Proficiency",
+    "Challenge",
+];
+
+/// Parse `s` with a rule of the grammar, suggesting a keyword if the error is on a misspelled one.
+fn parse_rule(rule: Rule, s: &str) -> Result<Pairs<'_, Rule>, Error> {
+    RequestParser::parse(rule, s).map_err(|error| {
+        let position = match error.location {
+            InputLocation::Pos(position) => position,
+            InputLocation::Span((start, _)) => start,
+        };
+        let word: String = s[position..]
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect();
+        Error::syntax(error, suggest_keyword(&word))
+    })
+}
+
+/// Closest keyword to a word that is not a keyword, if they are close enough to be a misspelling.
+fn suggest_keyword(word: &str) -> Option<String> {
+    let word = word.to_lowercase();
+    let (distance, keyword) = KEYWORDS
+        .iter()
+        .map(|keyword| (edit_distance(&word, &keyword.to_lowercase()), keyword))
+        .min()?;
+    if distance > 0 && distance <= 1 + word.len() / 4 {
+        Some(keyword.to_string())
+    } else {
+        None
+    }
+}
+
+/// Number of characters to insert, remove or replace to change a word into the other one.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let replace = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Read a number of the request, with an error pointing at it if it doesn't fit in its type.
+fn parse_number<T: FromStr>(
+    number: &pest::iterators::Pair<'_, Rule>,
+    what: &str,
+) -> Result<T, Error> {
+    number.as_str().trim().parse::<T>().map_err(|_| {
+        let message = format!("{} is too large for {}", number.as_str().trim(), what);
+        Error::syntax(
+            pest::error::Error::<Rule>::new_from_span(
+                ErrorVariant::CustomError { message },
+                number.as_span(),
+            ),
+            None,
+        )
+    })
+}
+
 impl FromStr for FudgeRoll {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            x if x == "+" => Ok(FudgeRoll::Plus),
-            x if x == "-" => Ok(FudgeRoll::Minus),
-            x if x == "0" => Ok(FudgeRoll::Blank),
+            "+" => Ok(FudgeRoll::Plus),
+            "-" => Ok(FudgeRoll::Minus),
+            "0" => Ok(FudgeRoll::Blank),
             _ => Err(Error::new(ErrorKind::Parse(format!(
                 "Can't read '{}' as a fudge roll value",
                 s
@@ -55,167 +155,692 @@ impl FromStr for FudgeSession {
 /// # Arguments
 /// * `s` Input string
 /// * `default_total` If set to `true`, in the absence of a parsed aggregation, the `ToTal` action will be applied to numeric rolls.
-/// This is allows users not to have to specify the Sum action each time they do a classic roll requiring the total.
+///   This is allows users not to have to specify the Sum action each time they do a classic roll requiring the total.
+///
+/// The rolls are bounded by the default [Limits](../../dice/struct.Limits.html):
+/// use [parse_request_with_dice](fn.parse_request_with_dice.html) with other limits if needed.
 pub fn parse_request(s: &str, default_total: bool) -> Result<MultiTypeSession, Error> {
-    match RequestParser::parse(Rule::roll_request, s) {
-        Err(err) => Err(Error::from(err)),
-        Ok(mut parsed_roll_request) => {
-            let mut num_request_dice: Vec<NumericRollRequest> = vec![];
-            let mut fudge_request_dice: Vec<FudgeRollRequest> = vec![];
-            let mut aggregation: Option<Aggregation> = None;
-            let mut actions: Vec<Action> = vec![];
-            for dice_or_action in parsed_roll_request.next().unwrap().into_inner() {
-                match dice_or_action.as_rule() {
-                    Rule::dice => {
-                        for dice in dice_or_action.into_inner() {
-                            let parsed_dice = parse_dice(dice)?;
-                            if let Some(dice) = parsed_dice.0 {
-                                num_request_dice.push(dice);
-                            }
-                            if let Some(dice) = parsed_dice.1 {
-                                fudge_request_dice.push(dice);
-                            }
+    parse_request_with_dice(s, default_total, DiceGenerator::new())
+}
+
+/// Try to parse a roll request from an input String, rolling with the given dice generator.
+///
+/// Use a seeded generator (cf. [DiceGenerator::from_seed](../../dice/struct.DiceGenerator.html#method.from_seed))
+/// to replay the exact same rolls.
+pub fn parse_request_with_dice(
+    s: &str,
+    default_total: bool,
+    dice: DiceGenerator,
+) -> Result<MultiTypeSession, Error> {
+    read_request(s)?.roll(default_total, dice)
+}
+
+/// Variables (like `$str = 3`) and macros (like `attack = 1D20 + $str`) that can be used in a request.
+///
+/// Both are replaced by their text before the request is read: a variable anywhere it appears,
+/// a macro wherever its name is a whole word (so a macro hides a dice ID with the same name).
+/// # Example
+/// ```
+/// # use letsroll::io::read::Definitions;
+/// let mut definitions = Definitions::new();
+/// definitions.define("$str", "3");
+/// definitions.define("attack", "1D20 + $str");
+/// assert_eq!(definitions.resolve("attack x2").unwrap(), "1D20 + 3 x2");
+/// assert!(definitions.resolve("1D20 + $dex").is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Definitions {
+    definitions: Vec<(String, String)>,
+}
+
+impl Definitions {
+    pub fn new() -> Definitions {
+        Definitions::default()
+    }
+
+    /// Define (or redefine) a variable, if the name starts with `$`, or a macro.
+    pub fn define(&mut self, name: &str, text: &str) {
+        match self
+            .definitions
+            .iter_mut()
+            .find(|(defined, _)| defined == name)
+        {
+            Some((_, defined_text)) => *defined_text = text.to_string(),
+            None => self.definitions.push((name.to_string(), text.to_string())),
+        }
+    }
+
+    /// Text of the variable or macro `name`, if it is defined.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.definitions
+            .iter()
+            .find(|(defined, _)| defined == name)
+            .map(|(_, text)| text.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /// All the definitions as (name, text), in the order they were first defined.
+    pub fn iter(&self) -> impl Iterator<Item = &(String, String)> {
+        self.definitions.iter()
+    }
+
+    /// Replace the variables and macros used in `s` by their text, until none is left.
+    pub fn resolve(&self, s: &str) -> Result<String, Error> {
+        self.resolve_with(s, &mut vec![])
+    }
+
+    fn resolve_with(&self, s: &str, resolving: &mut Vec<String>) -> Result<String, Error> {
+        let mut resolved = String::new();
+        let mut word = String::new();
+        // The extra separator flushes the last word
+        for c in s.chars().chain(std::iter::once(' ')) {
+            if c.is_ascii_alphanumeric() || c == '_' || (c == '$' && word.is_empty()) {
+                word.push(c);
+                continue;
+            }
+            resolved.push_str(&self.resolve_word(&word, resolving)?);
+            word.clear();
+            if c == '$' {
+                word.push(c);
+            } else {
+                resolved.push(c);
+            }
+        }
+        resolved.pop();
+        Ok(resolved)
+    }
+
+    fn resolve_word(&self, word: &str, resolving: &mut Vec<String>) -> Result<String, Error> {
+        match self.get(word) {
+            Some(text) => {
+                resolving.push(word.to_string());
+                if resolving[..resolving.len() - 1]
+                    .iter()
+                    .any(|name| name == word)
+                {
+                    return Err(Error::recursive_definition(resolving));
+                }
+                let resolved = self.resolve_with(text, resolving)?;
+                resolving.pop();
+                Ok(resolved)
+            }
+            None if word.starts_with('$') && word.len() > 1 => Err(Error::undefined_name(word)),
+            None => Ok(word.to_string()),
+        }
+    }
+}
+
+/// Split an input String between the definitions of variables and macros, and the request using them.
+///
+/// The definitions come first, one per line (or separated by `;`), and the request is the last line.
+/// # Example
+/// ```
+/// # use letsroll::io::read::read_document;
+/// let (definitions, request) = read_document("$str = 3\nattack = 1D20 + $str\nattack").unwrap();
+/// assert_eq!(definitions.get("$str"), Some("3"));
+/// assert_eq!(request, "attack");
+/// ```
+pub fn read_document(s: &str) -> Result<(Definitions, String), Error> {
+    let mut definitions = Definitions::new();
+    let mut request = String::new();
+    let document = parse_rule(Rule::roll_document, s)?.next().unwrap();
+    for pair in document.into_inner() {
+        match pair.as_rule() {
+            Rule::definition => {
+                let (name, text) = parse_definition(pair);
+                definitions.define(&name, &text);
+            }
+            Rule::request_body => request = pair.as_str().trim().to_string(),
+            Rule::EOI => (),
+            _ => unreachable!(),
+        }
+    }
+    Ok((definitions, request))
+}
+
+/// Read a single definition of a variable or a macro, like `$str = 3`, as (name, text).
+pub fn read_definition(s: &str) -> Result<(String, String), Error> {
+    let mut parsed = parse_rule(Rule::definition_line, s)?;
+    Ok(parse_definition(parsed.next().unwrap()))
+}
+
+fn parse_definition(definition: pest::iterators::Pair<'_, Rule>) -> (String, String) {
+    let mut inner = definition.into_inner();
+    let name = inner.next().unwrap().as_str().to_string();
+    let text = inner.next().unwrap().as_str().trim().to_string();
+    (name, text)
+}
+
+/// A request of a [RollFile](struct.RollFile.html), as it is written in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRequest {
+    pub label: Option<String>,
+    pub request: String,
+}
+
+/// Content of a `.roll` file: requests that share the same definitions of variables and macros.
+///
+/// In the file:
+/// * each line is a request, optionally labelled (`attack: 1D20 + $str`),
+///   and the lines starting with a space continue the request of the previous line,
+/// * or a definition (`$str = 3`, cf. [Definitions](struct.Definitions.html)), that applies to all the requests of the file,
+/// * or an include (`include common.roll`) of another file, relative to the including one,
+/// * `#` starts a comment until the end of the line.
+/// # Example
+/// ```
+/// # use letsroll::io::read::{read_request, RollFile};
+/// # use std::str::FromStr;
+/// let roll_file = RollFile::from_str("# My character\n$str = 3\nattack: 1D20 + $str\ndamage: 1D8 + $str\n").unwrap();
+/// assert_eq!(roll_file.requests().len(), 2);
+/// let attacks = roll_file.select(Some("attack")).unwrap();
+/// assert_eq!(attacks.read_request(&attacks.requests()[0]).unwrap(), read_request("1D20 + 3").unwrap());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RollFile {
+    definitions: Definitions,
+    requests: Vec<FileRequest>,
+}
+
+impl RollFile {
+    pub fn definitions(&self) -> &Definitions {
+        &self.definitions
+    }
+
+    pub fn requests(&self) -> &[FileRequest] {
+        &self.requests
+    }
+
+    /// Keep only the requests with the given label, or all of them if there is no label.
+    pub fn select(&self, label: Option<&str>) -> Result<RollFile, Error> {
+        let requests: Vec<FileRequest> = self
+            .requests
+            .iter()
+            .filter(|request| label.is_none() || request.label.as_deref() == label)
+            .cloned()
+            .collect();
+        match label {
+            Some(label) if requests.is_empty() => Err(Error::undefined_name(&format!(
+                "The request label {}",
+                label
+            ))),
+            _ => Ok(RollFile {
+                definitions: self.definitions.clone(),
+                requests,
+            }),
+        }
+    }
+
+    /// Read one of the requests, with the definitions of the file.
+    pub fn read_request(&self, request: &FileRequest) -> Result<MultiTypeRequest, Error> {
+        let (own_definitions, request) = read_document(&request.request)?;
+        let mut definitions = self.definitions.clone();
+        for (name, text) in own_definitions.iter() {
+            definitions.define(name, text);
+        }
+        read_resolved_request(&definitions.resolve(&request)?)
+    }
+
+    fn read_content(
+        &mut self,
+        content: &str,
+        directory: &Path,
+        including: &mut Vec<PathBuf>,
+    ) -> Result<(), Error> {
+        let parsed = parse_rule(Rule::roll_file, content)?.next().unwrap();
+        for pair in parsed.into_inner() {
+            match pair.as_rule() {
+                Rule::include => {
+                    let path = pair.into_inner().next().unwrap().as_str().trim();
+                    self.read_file(&directory.join(path), including)?;
+                }
+                Rule::definition => {
+                    let (name, text) = parse_definition(pair);
+                    self.definitions.define(&name, &text);
+                }
+                Rule::file_request => {
+                    let mut label = None;
+                    let mut lines: Vec<&str> = vec![];
+                    for part in pair.into_inner() {
+                        match part.as_rule() {
+                            Rule::request_label => label = Some(part.as_str().to_string()),
+                            Rule::request_line => lines.push(part.as_str().trim()),
+                            _ => unreachable!(),
                         }
                     }
-                    Rule::dice_and_action => {
-                        let mut dice_id: Option<String> = None;
-                        let mut dice: Option<(
-                            Option<NumericRollRequest>,
-                            Option<FudgeRollRequest>,
-                        )> = None;
-                        let mut dice_actions: Vec<Action> = vec![];
-                        for dice_or_dice_action in dice_or_action.into_inner() {
-                            match dice_or_dice_action.as_rule() {
-                                Rule::DICE_ID => {
-                                    dice_id = Some(dice_or_dice_action.as_str().to_string());
-                                }
-                                Rule::dice => {
-                                    dice = Some(parse_dice(
-                                        dice_or_dice_action.into_inner().next().unwrap(),
-                                    )?);
-                                }
-                                Rule::action => {
-                                    parse_action(
-                                        dice_or_dice_action.into_inner().next().unwrap(),
-                                        &mut dice_actions,
-                                    )?;
-                                }
-                                _ => unreachable!(),
+                    self.requests.push(FileRequest {
+                        label,
+                        request: lines.join(" "),
+                    });
+                }
+                Rule::EOI => (),
+                _ => unreachable!(),
+            }
+        }
+        Ok(())
+    }
+
+    fn read_file(&mut self, path: &Path, including: &mut Vec<PathBuf>) -> Result<(), Error> {
+        let canonical_path = path.canonicalize()?;
+        let included_twice = including.contains(&canonical_path);
+        including.push(canonical_path);
+        if included_twice {
+            return Err(Error::new(ErrorKind::File(format!(
+                "{} includes itself",
+                including
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<String>>()
+                    .join(" -> ")
+            ))));
+        }
+        let content = fs::read_to_string(path)?;
+        self.read_content(
+            &content,
+            path.parent().unwrap_or_else(|| Path::new("")),
+            including,
+        )?;
+        including.pop();
+        Ok(())
+    }
+}
+
+/// Includes are relative to the current directory.
+impl FromStr for RollFile {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut roll_file = RollFile::default();
+        roll_file.read_content(s, Path::new(""), &mut vec![])?;
+        Ok(roll_file)
+    }
+}
+
+/// Read a `.roll` file and the files it includes.
+pub fn read_roll_file<P: AsRef<Path>>(path: P) -> Result<RollFile, Error> {
+    let mut roll_file = RollFile::default();
+    roll_file.read_file(path.as_ref(), &mut vec![])?;
+    Ok(roll_file)
+}
+
+/// Try to read a roll request from an input String, without rolling any dice.
+///
+/// The request can be preceded by definitions of variables and macros (cf. [read_document](fn.read_document.html)).
+pub fn read_request(s: &str) -> Result<MultiTypeRequest, Error> {
+    let (definitions, request) = read_document(s)?;
+    read_resolved_request(&definitions.resolve(&request)?)
+}
+
+fn read_resolved_request(s: &str) -> Result<MultiTypeRequest, Error> {
+    let mut parsed_roll_request = parse_rule(Rule::roll_request, s)?;
+    let mut request = MultiTypeRequest::default();
+    let mut marked_expression = false;
+    for dice_or_action in parsed_roll_request.next().unwrap().into_inner() {
+        match dice_or_action.as_rule() {
+            Rule::dice => {
+                for dice in dice_or_action.into_inner() {
+                    let parsed_dice = parse_dice(dice)?;
+                    if let Some(dice) = parsed_dice.0 {
+                        request.numeric_requests.push(dice);
+                    }
+                    if let Some(dice) = parsed_dice.1 {
+                        request.fudge_requests.push(dice);
+                    }
+                    if let Some(dice) = parsed_dice.2 {
+                        request.symbol_requests.push(dice);
+                    }
+                }
+            }
+            Rule::dice_and_action => {
+                let ((numeric_dice, fudge_dice, symbol_dice), modifiers) =
+                    parse_dice_group(dice_or_action)?;
+                request.numeric_requests.extend(numeric_dice);
+                request.numeric_requests.extend(modifiers);
+                request.fudge_requests.extend(fudge_dice);
+                request.symbol_requests.extend(symbol_dice);
+            }
+            Rule::expression_mark => marked_expression = true,
+            Rule::expression => {
+                // Without parentheses, "2D6 + 3 x 2" would look like a list of dice
+                let parenthesized = dice_or_action
+                    .clone()
+                    .into_inner()
+                    .flatten()
+                    .any(|rule| rule.as_rule() == Rule::expression);
+                if !marked_expression && !parenthesized {
+                    return Err(Error::new(ErrorKind::Parse(String::from(
+                        "An arithmetic expression must start with \"=\" or use parentheses",
+                    ))));
+                }
+                let expression = parse_expression(dice_or_action, &mut request)?;
+                if request.numeric_requests.is_empty() && request.fudge_requests.is_empty() {
+                    return Err(Error::new(ErrorKind::Parse(String::from(
+                        "An arithmetic expression needs at least one dice",
+                    ))));
+                }
+                // A plain sum is read like a list of dice, to keep the detailed rolls
+                match sum_terms(&expression) {
+                    Some(terms) => {
+                        for (negative, term) in terms.iter() {
+                            if let (true, Expression::Fudge(index)) = (negative, term) {
+                                request.fudge_requests[*index] = request.fudge_requests[*index]
+                                    .clone()
+                                    .add_action(Action::Negate);
                             }
                         }
-                        if let Some(num_dice) = &dice.as_ref().unwrap().0 {
-                            num_request_dice
-                                .push(num_dice.clone().add_actions(dice_actions).add_id(dice_id));
-                            continue;
-                        }
-                        if let Some(fudge_dice) = &dice.as_ref().unwrap().1 {
-                            fudge_request_dice
-                                .push(fudge_dice.clone().add_actions(dice_actions).add_id(dice_id));
-                        }
+                        request.numeric_requests = terms
+                            .into_iter()
+                            .filter(|(_, term)| !matches!(term, Expression::Fudge(_)))
+                            .map(|(negative, term)| match term {
+                                Expression::Dice(index) if negative => request.numeric_requests
+                                    [*index]
+                                    .clone()
+                                    .add_action(Action::Negate),
+                                Expression::Dice(index) => request.numeric_requests[*index].clone(),
+                                Expression::Constant(value) => RollRequest::new(
+                                    1,
+                                    NumericDice::ConstDice(if negative { -value } else { *value }),
+                                ),
+                                Expression::Fudge(_) | Expression::Operation(..) => unreachable!(),
+                            })
+                            .collect()
                     }
-                    Rule::action => {
-                        for action in dice_or_action.into_inner() {
-                            parse_action(action, &mut actions)?;
+                    None => request.expression = Some(expression),
+                }
+            }
+            Rule::action => {
+                for action in dice_or_action.into_inner() {
+                    parse_action(action, &mut request.actions)?;
+                }
+            }
+            Rule::aggregation => {
+                for aggreg_action in dice_or_action.into_inner() {
+                    match aggreg_action.as_rule() {
+                        Rule::aggregation_count => {
+                            request.aggregation = Some(Aggregation::CountValues)
                         }
-                    }
-                    Rule::aggregation => {
-                        for aggreg_action in dice_or_action.into_inner() {
-                            match aggreg_action.as_rule() {
-                                Rule::aggregation_count => {
-                                    aggregation = Some(Aggregation::CountValues)
-                                }
-                                _ => unreachable!(),
-                            }
+                        Rule::aggregation_sum_by_id => {
+                            request.aggregation = Some(Aggregation::SumById)
+                        }
+                        Rule::aggregation_cancel => {
+                            request.aggregation = Some(Aggregation::CancelSymbols)
                         }
+                        _ => unreachable!(),
                     }
-                    Rule::EOI => (),
-                    _ => unreachable!(),
                 }
             }
+            Rule::EOI => (),
+            _ => unreachable!(),
+        }
+    }
+    Ok(request)
+}
 
-            let mut res = MultiTypeSession {
-                numeric_session: None,
-                fudge_session: None,
-            };
+/// Request read from a dice, in the field of its type.
+type ParsedDice = (
+    Option<NumericRollRequest>,
+    Option<FudgeRollRequest>,
+    Option<SymbolRollRequest>,
+);
 
-            if num_request_dice.len() > 0 {
-                let mut session = NumericSession::build_with_actions(num_request_dice)?;
-                session.add_actions(actions.clone())?;
-                if aggregation.is_some() {
-                    session = session.aggregate(&aggregation.unwrap());
-                } else if default_total && aggregation.is_none() && actions.len() == 0 {
-                    session.add_transformation(Action::Total)?;
-                }
-                res.numeric_session = Some(session);
-            }
-            if fudge_request_dice.len() > 0 {
-                let mut session = FudgeSession::build_with_actions(fudge_request_dice)?;
-                session.add_actions(actions)?;
-                if aggregation.is_some() {
-                    let mut num_session = session.aggregate(&aggregation.unwrap());
-                    let res_mut = &mut res;
-                    if res_mut.numeric_session.is_some() {
-                        res_mut
-                            .numeric_session
-                            .as_mut()
-                            .unwrap()
-                            .rolls
-                            .append(&mut num_session.rolls);
-                    } else {
-                        res.numeric_session = Some(num_session);
+/// Read a dice with its optional sign, ID, actions and modifiers.
+fn parse_dice_group(
+    group: pest::iterators::Pair<'_, Rule>,
+) -> Result<(ParsedDice, Vec<NumericRollRequest>), Error> {
+    let mut dice_id: Option<String> = None;
+    let mut dice: ParsedDice = (None, None, None);
+    let mut dice_actions: Vec<Action> = vec![];
+    let mut modifiers: Vec<NumericRollRequest> = vec![];
+    let mut negative = false;
+    for dice_or_dice_action in group.into_inner() {
+        match dice_or_dice_action.as_rule() {
+            Rule::dice_sign => {
+                negative = dice_or_dice_action.as_str() == "-";
+            }
+            Rule::DICE_ID => {
+                dice_id = Some(dice_or_dice_action.as_str().to_string());
+            }
+            Rule::dice | Rule::numeric_dice => {
+                dice = parse_dice(dice_or_dice_action.into_inner().next().unwrap())?;
+            }
+            Rule::numbered_dice | Rule::fudge_dice => {
+                dice = parse_dice(dice_or_dice_action)?;
+            }
+            Rule::action => {
+                parse_action(
+                    dice_or_dice_action.into_inner().next().unwrap(),
+                    &mut dice_actions,
+                )?;
+            }
+            Rule::group_modifier => {
+                // The grammar syntax enforce that a modifier is a numeric constant
+                let modifier = parse_dice(dice_or_dice_action.into_inner().next().unwrap())?;
+                modifiers.extend(modifier.0);
+            }
+            _ => unreachable!(),
+        }
+    }
+    // The subtraction applies to the result of the dice actions, and to the modifiers
+    if negative {
+        dice_actions.push(Action::Negate);
+    }
+    let modifiers = modifiers
+        .into_iter()
+        .map(|modifier| {
+            let modifier = modifier.add_id(dice_id.clone());
+            if negative {
+                modifier.add_action(Action::Negate)
+            } else {
+                modifier
+            }
+        })
+        .collect();
+    let dice = match dice {
+        (Some(num_dice), _, _) => (
+            Some(num_dice.add_actions(dice_actions).add_id(dice_id)),
+            None,
+            None,
+        ),
+        (_, Some(fudge_dice), _) => (
+            None,
+            Some(fudge_dice.add_actions(dice_actions).add_id(dice_id)),
+            None,
+        ),
+        (_, _, Some(symbol_dice)) => (
+            None,
+            None,
+            Some(symbol_dice.add_actions(dice_actions).add_id(dice_id)),
+        ),
+        _ => unreachable!(),
+    };
+    Ok((dice, modifiers))
+}
+
+/// Read an expression or a term, adding its dice groups to the numeric or fudge requests.
+fn parse_expression(
+    expression: pest::iterators::Pair<'_, Rule>,
+    request: &mut MultiTypeRequest,
+) -> Result<Expression, Error> {
+    let mut result: Option<Expression> = None;
+    let mut operator = Operator::Add;
+    for rule in expression.into_inner() {
+        let operand = match rule.as_rule() {
+            Rule::operator_add => {
+                operator = Operator::Add;
+                continue;
+            }
+            Rule::operator_subtract => {
+                operator = Operator::Subtract;
+                continue;
+            }
+            Rule::operator_multiply => {
+                operator = Operator::Multiply;
+                continue;
+            }
+            Rule::operator_divide => {
+                operator = Operator::Divide(Rounding::Down);
+                continue;
+            }
+            // The rounding follows the divisor, so the division is already built
+            Rule::rounding => {
+                if let Some(Expression::Operation(Operator::Divide(rounding), ..)) = &mut result {
+                    if rule.as_str().eq_ignore_ascii_case("up") {
+                        *rounding = Rounding::Up;
                     }
-                } else {
-                    res.fudge_session = Some(session);
                 }
+                continue;
             }
+            Rule::term | Rule::expression => parse_expression(rule, request)?,
+            Rule::expression_constant => Expression::Constant(parse_number(&rule, "a constant")?),
+            // The grammar syntax enforce that there are no symbol dice
+            Rule::expression_dice => match parse_dice_group(rule)?.0 {
+                (Some(num_dice), _, _) => {
+                    request.numeric_requests.push(num_dice);
+                    Expression::Dice(request.numeric_requests.len() - 1)
+                }
+                (_, Some(fudge_dice), _) => {
+                    request.fudge_requests.push(fudge_dice);
+                    Expression::Fudge(request.fudge_requests.len() - 1)
+                }
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        result = Some(match result {
+            None => operand,
+            Some(left) => Expression::Operation(operator, Box::new(left), Box::new(operand)),
+        });
+    }
+    // The grammar syntax enforce that there is at least one operand
+    Ok(result.unwrap())
+}
 
-            Ok(res)
+/// Dice and constants of an expression that only adds or subtracts them,
+/// with `true` for the subtracted ones.
+fn sum_terms(expression: &Expression) -> Option<Vec<(bool, &Expression)>> {
+    match expression {
+        Expression::Dice(_) | Expression::Fudge(_) | Expression::Constant(_) => {
+            Some(vec![(false, expression)])
+        }
+        Expression::Operation(operator, left, right) => {
+            let negative = match operator {
+                Operator::Add => false,
+                Operator::Subtract => true,
+                _ => return None,
+            };
+            let mut terms = sum_terms(left)?;
+            match **right {
+                Expression::Operation(..) => None,
+                _ => {
+                    terms.push((negative, right));
+                    Some(terms)
+                }
+            }
         }
     }
 }
 
-fn parse_dice(
-    dice: pest::iterators::Pair<'_, Rule>,
-) -> Result<(Option<NumericRollRequest>, Option<FudgeRollRequest>), Error> {
+fn parse_dice(dice: pest::iterators::Pair<'_, Rule>) -> Result<ParsedDice, Error> {
     match dice.as_rule() {
-        Rule::fudge_dice => Ok((None, Some(parse_fudge_dice(dice)?))),
-        Rule::num_const_dice => Ok((Some(parse_const_numeric_dice(dice)?), None)),
-        Rule::numbered_dice => Ok((Some(parse_numbered_dice(dice)?), None)),
+        Rule::fudge_dice => Ok((None, Some(parse_fudge_dice(dice)?), None)),
+        Rule::symbol_dice => Ok((None, None, Some(parse_symbol_dice(dice)?))),
+        Rule::num_const_dice => Ok((Some(parse_const_numeric_dice(dice)?), None, None)),
+        Rule::numbered_dice => Ok((Some(parse_numbered_dice(dice)?), None, None)),
         _ => unreachable!(),
     }
 }
 
 fn parse_numbered_dice(dice: pest::iterators::Pair<'_, Rule>) -> Result<NumericRollRequest, Error> {
     let mut dice_number: DiceNumber = 1;
-    let mut dice_sides: NumericRoll = 1;
+    let mut numeric_dice = NumericDice::NumberedDice(1);
+    let mut actions: Vec<Action> = vec![];
     for rule in dice.into_inner() {
         match rule.as_rule() {
             Rule::dice_number => {
-                dice_number = rule.as_str().parse::<DiceNumber>().unwrap();
+                dice_number = parse_number(&rule, "a number of dice")?;
             }
             Rule::dice_sides => {
-                dice_sides = rule.as_str().parse::<NumericRoll>().unwrap();
+                numeric_dice = NumericDice::NumberedDice(parse_number(&rule, "a number of sides")?);
+            }
+            Rule::dice_percent => numeric_dice = NumericDice::PercentileDice,
+            Rule::dice_faces => {
+                numeric_dice = NumericDice::CustomDice(
+                    rule.into_inner()
+                        .map(|face| parse_number(&face, "a dice face"))
+                        .collect::<Result<Vec<NumericRoll>, Error>>()?,
+                );
+            }
+            // The sides always come before the short actions
+            Rule::short_action => {
+                actions.push(parse_short_action(
+                    rule.into_inner().next().unwrap(),
+                    numeric_dice.get_max_value(),
+                )?);
+            }
+            // A spaced adv or dis comes after them, without its short_action pair
+            Rule::short_advantage | Rule::short_disadvantage => {
+                actions.push(parse_short_action(rule, numeric_dice.get_max_value())?);
             }
             _ => unreachable!(),
         }
     }
-    Ok(RollRequest::new(
-        dice_number,
-        NumericDice::NumberedDice(dice_sides),
-    ))
+    Ok(RollRequest::new(dice_number, numeric_dice).add_actions(actions))
+}
+
+/// Read an action of the community short notation, like `kh3` for `KeepBest(3)`.
+fn parse_short_action(
+    action: pest::iterators::Pair<'_, Rule>,
+    highest_face: NumericRoll,
+) -> Result<Action, Error> {
+    match action.as_rule() {
+        Rule::short_keep_best => Ok(Action::KeepBest(parse_positive_int(
+            action,
+            "a number of dice",
+        )?)),
+        Rule::short_keep_worst => Ok(Action::KeepWorst(parse_positive_int(
+            action,
+            "a number of dice",
+        )?)),
+        // Explode on the highest face
+        Rule::short_explode => Ok(Action::Explode(RollCondition::Values(vec![highest_face]))),
+        Rule::short_compound => Ok(Action::Compound(RollCondition::Values(vec![highest_face]))),
+        Rule::short_penetrate => Ok(Action::Penetrate(RollCondition::Values(vec![highest_face]))),
+        Rule::short_reroll => Ok(Action::RerollNumeric(
+            parse_roll_condition(action)?,
+            RerollMode::Once,
+        )),
+        Rule::short_successes => {
+            let mut inner = action.into_inner();
+            let comparison = parse_comparison(inner.next().unwrap().as_str());
+            let target = parse_number(&inner.next().unwrap(), "a roll value")?;
+            Ok(Action::CountSuccesses(comparison, target, vec![]))
+        }
+        Rule::short_advantage => Ok(Action::Advantage),
+        Rule::short_disadvantage => Ok(Action::Disadvantage),
+        _ => unreachable!(),
+    }
 }
 
 fn parse_const_numeric_dice(
     dice: pest::iterators::Pair<'_, Rule>,
 ) -> Result<NumericRollRequest, Error> {
-    let const_value: NumericRoll;
-    let rule = dice.into_inner().next().unwrap();
-    match rule.as_rule() {
-        Rule::dice_sides => {
-            const_value = rule.as_str().parse::<NumericRoll>().unwrap();
+    let mut const_value: NumericRoll = 0;
+    let mut sign: NumericRoll = 1;
+    for rule in dice.into_inner() {
+        match rule.as_rule() {
+            Rule::const_sign => {
+                if rule.as_str() == "-" {
+                    sign = -1;
+                }
+            }
+            Rule::dice_sides => {
+                const_value = parse_number(&rule, "a constant")?;
+            }
+            _ => unreachable!(),
         }
-        _ => unreachable!(),
     }
+    let const_value = sign * const_value;
     Ok(RollRequest::new(1, NumericDice::ConstDice(const_value)))
 }
 
@@ -224,7 +849,7 @@ fn parse_fudge_dice(dice: pest::iterators::Pair<'_, Rule>) -> Result<FudgeRollRe
     for rule in dice.into_inner() {
         match rule.as_rule() {
             Rule::dice_number => {
-                dice_number = rule.as_str().parse::<DiceNumber>().unwrap();
+                dice_number = parse_number(&rule, "a number of dice")?;
             }
             _ => unreachable!(),
         }
@@ -232,6 +857,26 @@ fn parse_fudge_dice(dice: pest::iterators::Pair<'_, Rule>) -> Result<FudgeRollRe
     Ok(RollRequest::new(dice_number, FudgeDice::FudgeDice))
 }
 
+fn parse_symbol_dice(dice: pest::iterators::Pair<'_, Rule>) -> Result<SymbolRollRequest, Error> {
+    let mut dice_number: DiceNumber = 1;
+    let mut symbol_dice = SymbolDice::Boost;
+    for rule in dice.into_inner() {
+        match rule.as_rule() {
+            Rule::dice_number => {
+                dice_number = parse_number(&rule, "a number of dice")?;
+            }
+            Rule::symbol_boost => symbol_dice = SymbolDice::Boost,
+            Rule::symbol_setback => symbol_dice = SymbolDice::Setback,
+            Rule::symbol_ability => symbol_dice = SymbolDice::Ability,
+            Rule::symbol_difficulty => symbol_dice = SymbolDice::Difficulty,
+            Rule::symbol_proficiency => symbol_dice = SymbolDice::Proficiency,
+            Rule::symbol_challenge => symbol_dice = SymbolDice::Challenge,
+            _ => unreachable!(),
+        }
+    }
+    Ok(RollRequest::new(dice_number, symbol_dice))
+}
+
 fn parse_action(
     action: pest::iterators::Pair<'_, Rule>,
     actions: &mut Vec<Action>,
@@ -241,28 +886,77 @@ fn parse_action(
         Rule::action_flip => actions.push(Action::FlipFlop),
         Rule::action_total => actions.push(Action::Total),
         Rule::action_concat => actions.push(Action::Concat),
+        Rule::action_advantage => actions.push(Action::Advantage),
+        Rule::action_disadvantage => actions.push(Action::Disadvantage),
+        Rule::action_critical => {
+            // Without condition, the highest face is a critical success
+            let condition = match action.clone().into_inner().next() {
+                Some(_) => Some(parse_roll_condition(action)?),
+                None => None,
+            };
+            actions.push(Action::Critical(condition));
+        }
+        Rule::action_critical_damage => actions.push(Action::CriticalDamage),
+        // Without number, a single bonus or penalty die is rolled
+        Rule::action_bonus => {
+            actions.push(Action::Bonus(match action.clone().into_inner().next() {
+                Some(_) => parse_positive_int(action, "a number of dice")?,
+                None => 1,
+            }))
+        }
+        Rule::action_penalty => {
+            actions.push(Action::Penalty(match action.clone().into_inner().next() {
+                Some(_) => parse_positive_int(action, "a number of dice")?,
+                None => 1,
+            }))
+        }
+        Rule::action_skill => {
+            actions.push(Action::SkillCheck(parse_positive_int(
+                action,
+                "a skill value",
+            )?));
+        }
         Rule::action_mult => {
-            actions.push(Action::MultiplyBy(parse_positive_int(action)?));
+            actions.push(Action::MultiplyBy(parse_positive_int(action, "a factor")?));
         }
-        Rule::action_reroll => {
+        Rule::action_reroll | Rule::action_reroll_until => {
             actions.push(parse_reroll_action(action)?);
         }
         Rule::action_explode => {
             actions.push(parse_explode_action(action)?);
         }
+        Rule::action_compound => {
+            actions.push(Action::Compound(parse_roll_condition(action)?));
+        }
+        Rule::action_penetrate => {
+            actions.push(Action::Penetrate(parse_roll_condition(action)?));
+        }
         Rule::action_keep_best => {
-            actions.push(Action::KeepBest(parse_positive_int(action)? as DiceNumber));
+            actions.push(Action::KeepBest(parse_positive_int(
+                action,
+                "a number of dice",
+            )?));
         }
         Rule::action_keep_worst => {
-            actions.push(Action::KeepWorst(parse_positive_int(action)? as DiceNumber));
+            actions.push(Action::KeepWorst(parse_positive_int(
+                action,
+                "a number of dice",
+            )?));
         }
         Rule::action_reroll_best => {
-            actions.push(Action::RerollBest(parse_positive_int(action)? as DiceNumber));
+            actions.push(Action::RerollBest(parse_positive_int(
+                action,
+                "a number of dice",
+            )?));
+        }
+        Rule::action_successes => {
+            actions.push(parse_successes_action(action)?);
         }
         Rule::action_reroll_worst => {
-            actions.push(Action::RerollWorst(
-                parse_positive_int(action)? as DiceNumber
-            ));
+            actions.push(Action::RerollWorst(parse_positive_int(
+                action,
+                "a number of dice",
+            )?));
         }
         _ => unreachable!(),
     };
@@ -270,63 +964,138 @@ fn parse_action(
 }
 
 fn parse_reroll_action(action: pest::iterators::Pair<'_, Rule>) -> Result<Action, Error> {
-    let mut num_values: Vec<NumericRoll> = vec![];
-    let mut fudge_values: Vec<FudgeRoll> = vec![];
+    let mode = match action.as_rule() {
+        Rule::action_reroll_until => RerollMode::Until,
+        _ => RerollMode::Once,
+    };
+    // The grammar syntax enforce that only values of the same type are present.
+    let is_fudge = action
+        .clone()
+        .into_inner()
+        .any(|rule| rule.as_rule() == Rule::fudge_roll_value);
+    if !is_fudge {
+        return Ok(Action::RerollNumeric(parse_roll_condition(action)?, mode));
+    }
+    let fudge_values: Result<Vec<FudgeRoll>, Error> = action
+        .into_inner()
+        .map(|rule| rule.as_str().parse::<FudgeRoll>())
+        .collect();
+    Ok(Action::RerollFudge(fudge_values?, mode))
+}
+
+fn parse_explode_action(action: pest::iterators::Pair<'_, Rule>) -> Result<Action, Error> {
+    // The grammar syntax enforce that only values of the same type are present.
+    let is_fudge = action
+        .clone()
+        .into_inner()
+        .any(|rule| rule.as_rule() == Rule::fudge_roll_value);
+    if !is_fudge {
+        return Ok(Action::Explode(parse_roll_condition(action)?));
+    }
+    let fudge_values: Result<Vec<FudgeRoll>, Error> = action
+        .into_inner()
+        .map(|rule| rule.as_str().parse::<FudgeRoll>())
+        .collect();
+    Ok(Action::ExplodeFudge(fudge_values?))
+}
+
+/// Read the numeric rolls triggering an action: a list of values, a threshold like `>=9` or a range like `1..3`.
+fn parse_roll_condition(action: pest::iterators::Pair<'_, Rule>) -> Result<RollCondition, Error> {
+    let mut values: Vec<NumericRoll> = vec![];
     for rule in action.into_inner() {
         match rule.as_rule() {
             Rule::num_roll_value => {
-                num_values.push(rule.as_str().parse::<NumericRoll>()?);
+                values.push(parse_number(&rule, "a roll value")?);
+            }
+            Rule::roll_threshold => {
+                let mut inner = rule.into_inner();
+                let comparison = parse_comparison(inner.next().unwrap().as_str());
+                let target = parse_number(&inner.next().unwrap(), "a roll value")?;
+                return Ok(RollCondition::Threshold(comparison, target));
             }
-            Rule::fudge_roll_value => {
-                fudge_values.push(rule.as_str().parse::<FudgeRoll>()?);
+            Rule::roll_range => {
+                let mut inner = rule.into_inner();
+                let low = parse_number(&inner.next().unwrap(), "a roll value")?;
+                let high = parse_number(&inner.next().unwrap(), "a roll value")?;
+                if low > high {
+                    return Err(Error::bad_action_parameter(&format!(
+                        "The range {}..{} is empty",
+                        low, high
+                    )));
+                }
+                return Ok(RollCondition::Range(low, high));
             }
             _ => unreachable!(),
         }
     }
-    // The grammar syntax enforce that there is at least one value
-    // and that only values of the same type are present.
-    match num_values.len() {
-        x if x > 0 => Ok(Action::RerollNumeric(num_values)),
-        _ => Ok(Action::RerollFudge(fudge_values)),
-    }
+    Ok(RollCondition::Values(values))
 }
 
-fn parse_explode_action(action: pest::iterators::Pair<'_, Rule>) -> Result<Action, Error> {
-    let mut num_values: Vec<NumericRoll> = vec![];
-    let mut fudge_values: Vec<FudgeRoll> = vec![];
+fn parse_successes_action(action: pest::iterators::Pair<'_, Rule>) -> Result<Action, Error> {
+    let mut comparison = Comparison::GreaterOrEqual;
+    let mut target: NumericRoll = 0;
+    let mut botch: Vec<NumericRoll> = vec![];
     for rule in action.into_inner() {
         match rule.as_rule() {
-            Rule::num_roll_value => {
-                num_values.push(rule.as_str().parse::<NumericRoll>()?);
+            Rule::action_successes_target => {
+                for target_rule in rule.into_inner() {
+                    match target_rule.as_rule() {
+                        Rule::comparison => comparison = parse_comparison(target_rule.as_str()),
+                        Rule::num_roll_value => {
+                            target = parse_number(&target_rule, "a roll value")?
+                        }
+                        _ => unreachable!(),
+                    }
+                }
             }
-            Rule::fudge_roll_value => {
-                fudge_values.push(rule.as_str().parse::<FudgeRoll>()?);
+            Rule::action_botch => {
+                for value in rule.into_inner() {
+                    botch.push(parse_number(&value, "a roll value")?);
+                }
             }
             _ => unreachable!(),
         }
     }
-    // The grammar syntax enforce that there is at least one value
-    // and that only values of the same type are present.
-    match num_values.len() {
-        x if x > 0 => Ok(Action::Explode(num_values)),
-        _ => Ok(Action::ExplodeFudge(fudge_values)),
+    Ok(Action::CountSuccesses(comparison, target, botch))
+}
+
+fn parse_comparison(comparison: &str) -> Comparison {
+    match comparison {
+        ">=" => Comparison::GreaterOrEqual,
+        "<=" => Comparison::LowerOrEqual,
+        ">" => Comparison::Greater,
+        "<" => Comparison::Lower,
+        _ => Comparison::Equal,
     }
 }
 
-fn parse_positive_int(action: pest::iterators::Pair<'_, Rule>) -> Result<u32, Error> {
+fn parse_positive_int<T: FromStr>(
+    action: pest::iterators::Pair<'_, Rule>,
+    what: &str,
+) -> Result<T, Error> {
     let rule = action.into_inner().next().unwrap();
     match rule.as_rule() {
-        Rule::POSITIVE_INT => Ok(rule.as_str().parse()?),
+        Rule::POSITIVE_INT => parse_number(&rule, what),
         _ => unreachable!(),
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::actions::{Action, Aggregation, Comparison, RerollMode, RollCondition};
     use crate::dice::*;
-    use crate::io::read::parse_request;
+    use crate::errors::ErrorKind;
+    use crate::io::read::{
+        parse_request, parse_request_with_dice, read_definition, read_document, read_request,
+        read_roll_file, FileRequest, RollFile,
+    };
     use crate::FudgeSession;
     use crate::NumericSession;
+    use crate::{Expression, Operator, Rounding};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::fs;
+    use std::path::Path;
     use std::str::FromStr;
 
     #[test]
@@ -356,6 +1125,101 @@ mod tests {
         );
     }
 
+    #[test]
+    fn read_custom_dice() {
+        let request = read_request("3D{0,0,1,1,2} 1d{-1,0,1}!").unwrap();
+        assert_eq!(
+            request.numeric_requests,
+            vec![
+                RollRequest::new(3, NumericDice::CustomDice(vec![0, 0, 1, 1, 2])),
+                RollRequest::new(1, NumericDice::CustomDice(vec![-1, 0, 1]))
+                    .add_action(Action::Explode(RollCondition::Values(vec![1]))),
+            ]
+        );
+        let session = parse_request("10D{2,3,3,4,4,5}", false).unwrap();
+        let rolls = &session.numeric_raw_rolls()[0].rolls;
+        assert_eq!(rolls.len(), 10);
+        assert!(rolls.iter().all(|roll| (2..=5).contains(roll)));
+
+        for invalid in ["3D{}", "3D{1,}", "3D{1 2}", "3D{a}"].iter() {
+            assert!(read_request(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn read_custom_dice_extreme_faces() {
+        let request = read_request("1D{-2147483648} 1D{2147483647}").unwrap();
+        assert_eq!(
+            request.numeric_requests,
+            vec![
+                RollRequest::new(1, NumericDice::CustomDice(vec![NumericRoll::MIN])),
+                RollRequest::new(1, NumericDice::CustomDice(vec![NumericRoll::MAX])),
+            ]
+        );
+        // The results that don't fit in a roll are errors
+        for overflowing in [
+            "1D{2147483647} 1D{2147483647}",
+            "1D{2147483647} 1D{2147483647} Total",
+            "-(1D{-2147483648})",
+            "1D{-2147483648} Flip",
+            "3D{-2147483648} Penetrate(<2)",
+            "= 1D{-2147483648} - 1",
+        ]
+        .iter()
+        {
+            let result = read_request(overflowing)
+                .unwrap()
+                .roll(true, DiceGenerator::new());
+            assert!(result.is_err(), "{}", overflowing);
+        }
+    }
+
+    #[test]
+    fn read_percentile_dice() {
+        let request = read_request("(D% Bonus) (luck 1d% Penalty(2) Skill(45))").unwrap();
+        assert_eq!(
+            request.numeric_requests,
+            vec![
+                RollRequest::new(1, NumericDice::PercentileDice).add_action(Action::Bonus(1)),
+                RollRequest::new(1, NumericDice::PercentileDice)
+                    .add_id(Some(String::from("luck")))
+                    .add_action(Action::Penalty(2))
+                    .add_action(Action::SkillCheck(45)),
+            ]
+        );
+        let session = parse_request("50D%", false).unwrap();
+        let rolls = &session.numeric_raw_rolls()[0].rolls;
+        assert!(rolls.iter().all(|roll| (1..=100).contains(roll)));
+
+        for invalid in ["D% Bonus(0)", "D% Skill", "D% Skill(-5)", "D%%"].iter() {
+            assert!(read_request(invalid).is_err(), "{}", invalid);
+        }
+        match parse_request("1D20 Bonus", false).unwrap_err().kind() {
+            ErrorKind::IncompatibleAction(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+
+        // The success levels can't be added up
+        for totaled in [
+            "2D% Skill(50) Total",
+            "(1D% Skill(50)) 1D6 Total",
+            "(1D% Skill(50) Sum)",
+            "(2D% Skill(50)) x2 Total Successes(>=2)",
+            "= (1D% Skill(50)) x 2",
+        ]
+        .iter()
+        {
+            match parse_request(totaled, true).unwrap_err().kind() {
+                ErrorKind::IncompatibleAction(_) => (),
+                kind => panic!("Unexpected error {:?} for {}", kind, totaled),
+            }
+        }
+        // Nor are they totaled by default
+        let session = parse_request("(1D% Skill(50)) 1D6", true).unwrap();
+        assert_eq!(session.total(), None);
+        assert_eq!(session.numeric_session.unwrap().rolls.len(), 2);
+    }
+
     #[test]
     fn read_fudge_dice() {
         let requests = &FudgeSession::from_str(&String::from("F")).unwrap().requests;
@@ -367,6 +1231,29 @@ mod tests {
         assert_eq!(*requests, vec![RollRequest::new(10, FudgeDice::FudgeDice)]);
     }
 
+    #[test]
+    fn read_symbol_dice() {
+        let request = read_request("2Ability (fear 1setback) Proficiency Cancel").unwrap();
+        assert_eq!(
+            request.symbol_requests,
+            vec![
+                RollRequest::new(2, SymbolDice::Ability),
+                RollRequest::new(1, SymbolDice::Setback).add_id(Some(String::from("fear"))),
+                RollRequest::new(1, SymbolDice::Proficiency),
+            ]
+        );
+        assert_eq!(request.aggregation, Some(Aggregation::CancelSymbols));
+        // A constant followed by a symbol dice is a negated group
+        assert!(read_request("-2Ability")
+            .unwrap()
+            .numeric_requests
+            .is_empty());
+
+        for invalid in ["2Abilities", "2Ability!", "2 Ability"].iter() {
+            assert!(read_request(invalid).is_err(), "{}", invalid);
+        }
+    }
+
     #[test]
     fn read_const_dice() {
         let requests = &NumericSession::from_str(&String::from("+5"))
@@ -386,6 +1273,309 @@ mod tests {
         );
     }
 
+    #[test]
+    fn read_negative_dice() {
+        let request = read_request("1D20 -2 - 3").unwrap();
+        assert_eq!(
+            request.numeric_requests,
+            vec![
+                RollRequest::new(1, NumericDice::NumberedDice(20)),
+                RollRequest::new(1, NumericDice::ConstDice(-2)),
+                RollRequest::new(1, NumericDice::ConstDice(-3)),
+            ]
+        );
+
+        let request = read_request("2D6 - 1D4 -(FIRE 4D6 KeepBest(3)) +1D8").unwrap();
+        assert_eq!(
+            request.numeric_requests,
+            vec![
+                RollRequest::new(2, NumericDice::NumberedDice(6)),
+                RollRequest::new(1, NumericDice::NumberedDice(4)).add_action(Action::Negate),
+                RollRequest::new(4, NumericDice::NumberedDice(6))
+                    .add_id(Some(String::from("FIRE")))
+                    .add_action(Action::KeepBest(3))
+                    .add_action(Action::Negate),
+                RollRequest::new(1, NumericDice::NumberedDice(8)),
+            ]
+        );
+
+        let session = parse_request("+2 -5 -(+4)", true).unwrap();
+        assert_eq!(session.numeric_session.unwrap().rolls[0].rolls, vec![-7]);
+
+        read_request("2D6 -").unwrap_err();
+        read_request("2D6 - x2").unwrap_err();
+    }
+
+    #[test]
+    fn read_expression() {
+        let request = read_request("(2D6 + 3) x 2").unwrap();
+        assert_eq!(
+            request.numeric_requests,
+            vec![RollRequest::new(2, NumericDice::NumberedDice(6))]
+        );
+        assert_eq!(
+            request.expression,
+            Some(Expression::Operation(
+                Operator::Multiply,
+                Box::new(Expression::Operation(
+                    Operator::Add,
+                    Box::new(Expression::Dice(0)),
+                    Box::new(Expression::Constant(3))
+                )),
+                Box::new(Expression::Constant(2))
+            ))
+        );
+
+        // Multiplications and divisions are done first
+        let request = read_request("= 1D8 + (FIRE 1D6 KeepBest(1)) * 2 - 10 / 3 up").unwrap();
+        assert_eq!(
+            request.expression,
+            Some(Expression::Operation(
+                Operator::Subtract,
+                Box::new(Expression::Operation(
+                    Operator::Add,
+                    Box::new(Expression::Dice(0)),
+                    Box::new(Expression::Operation(
+                        Operator::Multiply,
+                        Box::new(Expression::Dice(1)),
+                        Box::new(Expression::Constant(2))
+                    ))
+                )),
+                Box::new(Expression::Operation(
+                    Operator::Divide(Rounding::Up),
+                    Box::new(Expression::Constant(10)),
+                    Box::new(Expression::Constant(3))
+                ))
+            ))
+        );
+        assert_eq!(
+            request.numeric_requests[1],
+            RollRequest::new(1, NumericDice::NumberedDice(6))
+                .add_id(Some(String::from("FIRE")))
+                .add_action(Action::KeepBest(1))
+        );
+
+        // A plain sum is read as a list of dice
+        let request = read_request("1D8 + 1D6 + 4 - 1").unwrap();
+        assert_eq!(request.expression, None);
+        assert_eq!(
+            request.numeric_requests,
+            vec![
+                RollRequest::new(1, NumericDice::NumberedDice(8)),
+                RollRequest::new(1, NumericDice::NumberedDice(6)),
+                RollRequest::new(1, NumericDice::ConstDice(4)),
+                RollRequest::new(1, NumericDice::ConstDice(-1)),
+            ]
+        );
+
+        let session = parse_request("(2D1 + 3) / 4 up x 3", true).unwrap();
+        assert_eq!(
+            session.to_string(),
+            "Detailed rolls\t: (((2D1:2) + 3 = 5) / 4 up = 2) x 3\nRESULT \t: 6"
+        );
+        assert_eq!(session.expression().unwrap().value(), 6);
+        let session = parse_request("(2D1 - 5) / 2 DOWN", true).unwrap();
+        assert_eq!(session.expression().unwrap().value(), -2);
+
+        parse_request("2D6 / (1D1 - 1D1) up", true).unwrap_err();
+        read_request("2D6 / 2").unwrap_err();
+        read_request("(2 + 3) x 2").unwrap_err();
+        read_request("(4F + 1) x 2 Total").unwrap_err();
+
+        // Fudge dice count for their value
+        let request = read_request("(4F + 1) x 2").unwrap();
+        assert_eq!(
+            request.expression,
+            Some(Expression::Operation(
+                Operator::Multiply,
+                Box::new(Expression::Operation(
+                    Operator::Add,
+                    Box::new(Expression::Fudge(0)),
+                    Box::new(Expression::Constant(1))
+                )),
+                Box::new(Expression::Constant(2))
+            ))
+        );
+        assert_eq!(
+            request.fudge_requests,
+            vec![RollRequest::new(4, FudgeDice::FudgeDice)]
+        );
+        let session = parse_request("(2F + 1D1) x 2", true).unwrap();
+        let fudge_total: i64 = session.fudge_raw_rolls()[0]
+            .rolls
+            .iter()
+            .map(|roll| roll.value())
+            .sum();
+        assert_eq!(session.total(), Some(2 * (fudge_total as i32 + 1)));
+        assert_eq!(
+            read_request("1D6 - 4F").unwrap(),
+            read_request("1D6 -4F").unwrap()
+        );
+        assert_eq!(
+            read_request("1D6 - 4F").unwrap().fudge_requests[0].actions,
+            vec![Action::Negate]
+        );
+        read_request("(2D6 + 3) x 2 Count").unwrap_err();
+    }
+
+    #[test]
+    fn read_list_or_expression() {
+        // Global actions apply to each dice group of a list
+        let request = read_request("2D6 +3 x2").unwrap();
+        assert_eq!(request.expression, None);
+        assert_eq!(
+            request.numeric_requests,
+            vec![
+                RollRequest::new(2, NumericDice::NumberedDice(6)),
+                RollRequest::new(1, NumericDice::ConstDice(3)),
+            ]
+        );
+        assert_eq!(request.actions, vec![Action::MultiplyBy(2)]);
+        let session = parse_request("2D1 +3 x2", true).unwrap();
+        assert_eq!(session.to_string(), "(2D1) x 2: 2 2\n(1+3) x 2: 6");
+
+        // An expression starts with "=" or has parentheses
+        let request = read_request("= 2D6 + 3 x 2").unwrap();
+        assert_eq!(
+            request.expression,
+            Some(Expression::Operation(
+                Operator::Add,
+                Box::new(Expression::Dice(0)),
+                Box::new(Expression::Operation(
+                    Operator::Multiply,
+                    Box::new(Expression::Constant(3)),
+                    Box::new(Expression::Constant(2))
+                ))
+            ))
+        );
+        assert_eq!(
+            *read_request("2D6 * 2").unwrap_err().kind(),
+            ErrorKind::Parse(String::from(
+                "An arithmetic expression must start with \"=\" or use parentheses"
+            ))
+        );
+        assert_eq!(
+            read_request("= 1D8 + 1D6 + 4 - 1").unwrap(),
+            read_request("1D8 + 1D6 + 4 - 1").unwrap()
+        );
+    }
+
+    #[test]
+    fn read_short_notation() {
+        let same_requests = |short: &str, long: &str| {
+            assert_eq!(
+                read_request(short).unwrap().numeric_requests,
+                read_request(long).unwrap().numeric_requests
+            )
+        };
+        same_requests("4d6kh3", "(4D6 KeepBest(3))");
+        same_requests("4d6k3", "(4D6 KeepBest(3))");
+        same_requests("2d20kl1", "(2D20 KeepWorst(1))");
+        same_requests("3d6!", "(3D6 Explode(6))");
+        same_requests("1d10!!", "(1D10 Compound(10))");
+        same_requests("1d6!p", "(1D6 Penetrate(6))");
+        same_requests("(1d10 Explode(=9))", "(1D10 Explode(==9))");
+        same_requests("4d6r1", "(4D6 Reroll(1))");
+        same_requests("4d6r1r2", "(4D6 Reroll(1,2))");
+        same_requests("8d10>=7", "(8D10 Successes(>=7))");
+        // d% is the percentile dice, still rolled from 1 to 100
+        same_requests("d%", "1D%");
+        same_requests("d%!", "(D% Explode(100))");
+        same_requests("1d20adv +5", "(1D20 Advantage) +5");
+        same_requests("1d20dis", "(1D20 dis)");
+        same_requests("(1D20 adv)", "(1D20 Advantage)");
+        // Spaced, adv and dis still belong to their dice when other dice follow
+        same_requests("1d20 adv +5", "(1D20 Advantage) +5");
+        same_requests("1d20 dis - 1 (dmg 1D8)", "(1D20 Disadvantage) -1 (dmg 1D8)");
+        // At the end of the request, they apply to all the groups
+        assert_eq!(
+            read_request("2D6 1d20 adv").unwrap().actions,
+            vec![Action::Advantage]
+        );
+
+        // Both notations can be mixed, the short actions come first
+        same_requests(
+            "(FIRE 4d6r1kh3 x2) 2d20kl1 + 5",
+            "(FIRE 4D6 Reroll(1) KeepBest(3) x2) (2D20 KeepWorst(1)) +5",
+        );
+        assert!(read_request("4d6KeepBest(3)").is_ok());
+
+        read_request("4d6kh").unwrap_err();
+        read_request("4d6 kh3").unwrap_err();
+        read_request("4F!").unwrap_err();
+        read_request("1d20advantage").unwrap_err();
+    }
+
+    #[test]
+    fn read_roll_conditions() {
+        let actions = |request: &str| read_request(request).unwrap().actions;
+        assert_eq!(
+            actions("1D20 Reroll(<3)"),
+            vec![Action::RerollNumeric(
+                RollCondition::Threshold(Comparison::Lower, 3),
+                RerollMode::Once
+            )]
+        );
+        assert_eq!(
+            actions("1D20 RerollUntil(1..3)"),
+            vec![Action::RerollNumeric(
+                RollCondition::Range(1, 3),
+                RerollMode::Until
+            )]
+        );
+        assert_eq!(
+            actions("4F RerollUntil(-)"),
+            vec![Action::RerollFudge(
+                vec![FudgeRoll::Minus],
+                RerollMode::Until
+            )]
+        );
+        assert_eq!(
+            actions("1D10 Explode(>=9)"),
+            vec![Action::Explode(RollCondition::Threshold(
+                Comparison::GreaterOrEqual,
+                9
+            ))]
+        );
+        // The short notation applies to the dice group
+        let group_actions = |request: &str| {
+            read_request(request).unwrap().numeric_requests[0]
+                .actions
+                .clone()
+        };
+        assert_eq!(group_actions("1d20r<3"), actions("1D20 Reroll(<3)"));
+        assert_eq!(group_actions("1d20r1..2"), actions("1D20 Reroll(1..2)"));
+
+        match read_request("1D20 Reroll(3..1)").unwrap_err().kind() {
+            ErrorKind::BadActionParameter(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+        read_request("1D20 Reroll(<3,4)").unwrap_err();
+        // Fudge dice can only be rerolled on their values
+        assert!(read_request("4F Reroll(<3)")
+            .unwrap()
+            .roll(true, DiceGenerator::new())
+            .is_err());
+    }
+
+    #[test]
+    fn read_critical() {
+        let request = read_request("(HIT 1D20 Crit) (HIT +5) (DMG 1D8 CritDamage) +3").unwrap();
+        assert_eq!(
+            request.numeric_requests[0].actions,
+            vec![Action::Critical(None)]
+        );
+        assert_eq!(
+            request.numeric_requests[2].actions,
+            vec![Action::CriticalDamage]
+        );
+        assert_eq!(
+            read_request("(1D20 Crit(19,20))").unwrap().numeric_requests[0].actions,
+            vec![Action::Critical(Some(RollCondition::Values(vec![19, 20])))]
+        );
+        read_request("(1D20 Crit())").unwrap_err();
+    }
+
     #[test]
     fn read_request_with_id() {
         let requests = &NumericSession::from_str(&String::from("(FIRE +5)"))
@@ -414,6 +1604,322 @@ mod tests {
         assert!(!&NumericSession::from_str(&String::from("_ABC +5")).is_ok());
     }
 
+    #[test]
+    fn read_sum_by_id() {
+        let session = parse_request(
+            &String::from("(FIRE 2D1) (FORCE +4) (FIRE +3) SumById"),
+            true,
+        )
+        .unwrap()
+        .numeric_session
+        .unwrap();
+        assert_eq!(
+            session
+                .rolls
+                .iter()
+                .map(|rolls| rolls.to_string())
+                .collect::<Vec<String>>(),
+            vec!["SUM(FIRE): 5", "SUM(FORCE): 4", "TOTAL: 9"]
+        );
+
+        // Sum and SumById must not be confused
+        assert!(parse_request(&String::from("2D6 Sum"), true).is_ok());
+        // Fudge rolls can't be summed
+        parse_request(&String::from("(FIRE 4F) SumById"), true).unwrap_err();
+    }
+
+    #[test]
+    fn read_group_modifiers() {
+        // The modifiers are groups with the same ID
+        assert_eq!(
+            read_request("(hit 1D20 +5)").unwrap(),
+            read_request("(hit 1D20) (hit +5)").unwrap()
+        );
+        assert_eq!(
+            read_request("-(hit 1D20 KeepBest(1) +5 - 1)").unwrap(),
+            read_request("-(hit 1D20 KeepBest(1)) -(hit +5) -(hit -1)").unwrap()
+        );
+        let session = parse_request("(hit 2D1 +5) (dmg 1D1 +2) SumById", true).unwrap();
+        assert_eq!(session.to_string(), "SUM(hit): 7\nSUM(dmg): 3\nTOTAL: 10");
+
+        // Without a label, it is an arithmetic expression
+        assert!(read_request("(1D20 +5) x2").unwrap().expression.is_some());
+        read_request("(hit 1D20 +1D4)").unwrap_err();
+    }
+
+    #[test]
+    fn read_with_seeded_dice() {
+        let request = "10D20 (DMG 4D6 KeepBest(3)) 5F";
+        let first = parse_request_with_dice(request, false, DiceGenerator::from_seed(7)).unwrap();
+        let second = parse_request_with_dice(request, false, DiceGenerator::from_seed(7)).unwrap();
+        assert_eq!(first.to_string(), second.to_string());
+    }
+
+    #[test]
+    fn read_successes() {
+        let request = read_request("(8D10 Successes(>=8) Botch(1)) 5D6 Successes(=6)").unwrap();
+        assert_eq!(
+            request.numeric_requests[0].actions,
+            vec![Action::CountSuccesses(
+                Comparison::GreaterOrEqual,
+                8,
+                vec![1]
+            )]
+        );
+        assert_eq!(
+            request.actions,
+            vec![Action::CountSuccesses(Comparison::Equal, 6, vec![])]
+        );
+        assert_eq!(
+            read_request("3D6 Successes(<=2) Botch(5,6)")
+                .unwrap()
+                .actions,
+            vec![Action::CountSuccesses(
+                Comparison::LowerOrEqual,
+                2,
+                vec![5, 6]
+            )]
+        );
+        assert_eq!(
+            read_request("3D6 Successes(==2)").unwrap().actions,
+            vec![Action::CountSuccesses(Comparison::Equal, 2, vec![])]
+        );
+
+        read_request("3D6 Botch(1)").unwrap_err();
+        read_request("3D6 Successes(8)").unwrap_err();
+        assert_eq!(
+            read_request("3D6 Successes(>5)").unwrap().actions,
+            vec![Action::CountSuccesses(Comparison::Greater, 5, vec![])]
+        );
+    }
+
+    #[test]
+    fn read_definitions() {
+        let (definitions, request) =
+            read_document("\n$str = 3\nattack = (hit 1D20) + $str ; $str=4\nattack x2\n").unwrap();
+        assert_eq!(request, "attack x2");
+        assert_eq!(
+            definitions
+                .iter()
+                .cloned()
+                .collect::<Vec<(String, String)>>(),
+            vec![
+                (String::from("$str"), String::from("4")),
+                (String::from("attack"), String::from("(hit 1D20) + $str")),
+            ]
+        );
+        assert_eq!(definitions.resolve(&request).unwrap(), "(hit 1D20) + 4 x2");
+        assert_eq!(
+            read_request("$str = 3; attack = 1D20 + $str; attack x2").unwrap(),
+            read_request("1D20 + 3 x2").unwrap()
+        );
+
+        // A comparison in a short notation is not a definition
+        assert_eq!(read_document("d10=7").unwrap().1, "d10=7");
+        assert_eq!(
+            read_definition("$dex = 2").unwrap(),
+            (String::from("$dex"), String::from("2"))
+        );
+        read_definition("2x = 1D6").unwrap_err();
+
+        for (request, expected) in [
+            ("1D20 + $dex", "Undefined name error: $dex is not defined"),
+            (
+                "a = b; b = 1D6 + a; a",
+                "Recursive definition error: a -> b -> a is defined with itself",
+            ),
+            (
+                "$a = $a; 1D6 + $a",
+                "Recursive definition error: $a -> $a is defined with itself",
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(read_request(request).unwrap_err().to_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn read_roll_files() {
+        let sheet = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/rolls/sheet.roll");
+        let roll_file = read_roll_file(&sheet).unwrap();
+        assert_eq!(
+            roll_file
+                .requests()
+                .iter()
+                .map(|request| request.label.as_deref().unwrap())
+                .collect::<Vec<&str>>(),
+            vec!["attack", "damage", "save", "initiative"]
+        );
+        assert_eq!(roll_file.definitions().get("$prof"), Some("2"));
+        for (label, expected) in [
+            ("attack", "1D20 + 3 + 2"),
+            ("damage", "(SLASH 1D8) + 3"),
+            ("initiative", "2D20 KeepBest(1)"),
+        ]
+        .iter()
+        {
+            let selected = roll_file.select(Some(label)).unwrap();
+            assert_eq!(selected.requests().len(), 1);
+            assert_eq!(
+                selected.read_request(&selected.requests()[0]).unwrap(),
+                read_request(expected).unwrap()
+            );
+        }
+        assert_eq!(roll_file.select(None).unwrap(), roll_file);
+        match roll_file.select(Some("fireball")).unwrap_err().kind() {
+            ErrorKind::UndefinedName(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+
+        // The written file is read the same
+        let written = RollFile::from_str(&roll_file.to_string()).unwrap();
+        assert_eq!(written, roll_file);
+
+        let roll_file =
+            RollFile::from_str("\n2D6 # no label\n\n  # indented comment\nx: 1D6 ; $a = 2\n")
+                .unwrap();
+        assert_eq!(
+            roll_file.requests().to_vec(),
+            vec![
+                FileRequest {
+                    label: None,
+                    request: String::from("2D6")
+                },
+                FileRequest {
+                    label: Some(String::from("x")),
+                    request: String::from("1D6 ; $a = 2")
+                },
+            ]
+        );
+        RollFile::from_str("a = 1D6 ; x: 2D6").unwrap_err();
+
+        // Files including each other
+        let directory = std::env::temp_dir().join("letsroll_read_roll_files");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("first.roll"), "include second.roll\n1D6").unwrap();
+        fs::write(directory.join("second.roll"), "include first.roll\n").unwrap();
+        match read_roll_file(directory.join("first.roll"))
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::File(message) => assert!(message.ends_with("first.roll includes itself")),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+        match read_roll_file(directory.join("third.roll"))
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::File(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn read_syntax_errors() {
+        let syntax_error = |request: &str| match read_request(request).unwrap_err().kind() {
+            ErrorKind::Syntax(error) => error.clone(),
+            kind => panic!("Unexpected error {:?} for {}", kind, request),
+        };
+
+        let error = syntax_error("4D6 KepBest(3)");
+        assert_eq!(error.start, (1, 5));
+        assert_eq!(error.end, None);
+        assert!(error.expected.contains(&String::from("action")));
+        assert_eq!(error.suggestion, Some(String::from("KeepBest")));
+        assert!(error.to_string().ends_with("did you mean KeepBest?"));
+        assert_eq!(
+            syntax_error("2D6 totl").suggestion,
+            Some(String::from("Total"))
+        );
+        assert_eq!(
+            syntax_error("2D6 rerol(1)").suggestion,
+            Some(String::from("Reroll"))
+        );
+        assert_eq!(syntax_error("2D6 Fireball").suggestion, None);
+        assert_eq!(syntax_error("2D6\n3D6").start, (2, 1));
+
+        // Numbers too large for their type are errors on their span
+        let error = syntax_error("300D6");
+        assert_eq!((error.start, error.end), ((1, 1), Some((1, 4))));
+        assert!(error.expected.is_empty());
+        assert!(error
+            .message
+            .contains("300 is too large for a number of dice"));
+        for request in [
+            "1D99999999999",
+            "3D6 KeepBest(256)",
+            "(+99999999999)",
+            "2D6 Reroll(99999999999)",
+            "8D10>=99999999999",
+            "1D6 + 99999999999 x2",
+        ]
+        .iter()
+        {
+            syntax_error(request);
+        }
+    }
+
+    #[test]
+    fn read_any_input() {
+        let tokens = [
+            "1",
+            "9",
+            "0",
+            "99999999999",
+            "D",
+            "d",
+            "F",
+            "%",
+            "+",
+            "-",
+            "x",
+            "*",
+            "/",
+            "(",
+            ")",
+            ",",
+            " ",
+            "=",
+            ">=",
+            "<=",
+            "!",
+            "k",
+            "kh",
+            "kl",
+            "r",
+            "up",
+            "down",
+            "Sum",
+            "Flip",
+            "Concat",
+            "Total",
+            "KeepBest(",
+            "Reroll(",
+            "Explode(",
+            "Successes(",
+            "Botch(",
+            "Count",
+            "SumById",
+            "FIRE",
+            "$a",
+            "a",
+            ";",
+            "\n",
+            "#",
+            ":",
+        ];
+        let mut rng = StdRng::seed_from_u64(15);
+        for _ in 0..5000 {
+            let request: String = (0..rng.gen_range(1, 12))
+                .map(|_| tokens[rng.gen_range(0, tokens.len())])
+                .collect();
+            // Any input is either read or rejected, without panicking
+            read_request(&request).ok();
+            RollFile::from_str(&request).ok();
+        }
+    }
+
     // // TODO add test for global actions + dice actions + KO tests for incompatibility
     #[test]
     fn read_ko() {
@@ -427,5 +1933,4 @@ mod tests {
         parse_request(&String::from("2+8"), false).unwrap_err();
         parse_request(&String::from("5D 20"), false).unwrap_err();
     }
-
 }
@@ -0,0 +1,189 @@
+//! `repl` keeps a roll session alive between requests, for the interactive mode of the command line.
+//!
+//! Besides roll requests, a line can be:
+//! * `!!` to roll the last request again,
+//! * `history` to list the past requests with their results,
+//! * `:def <name> = <request>` to define a macro (or `:def $<name> = <value>` for a variable),
+//!   that can then be used in later requests (cf. [Definitions](../read/struct.Definitions.html)).
+
+use crate::dice::DiceGenerator;
+use crate::errors::{Error, ErrorKind};
+use crate::io::read::{parse_request_with_dice, read_definition, Definitions};
+use crate::MultiTypeSession;
+
+const REPEAT: &str = "!!";
+const HISTORY: &str = "history";
+const DEFINE: &str = ":def";
+
+/// Interactive session: all the requests are rolled with the same dice generator.
+///
+/// # Example
+/// ```
+/// # use letsroll::dice::DiceGenerator;
+/// # use letsroll::io::repl::Repl;
+/// let mut repl = Repl::new(DiceGenerator::from_seed(42));
+/// repl.eval(":def attack = (hit 1D20 +5)").unwrap();
+/// repl.eval("attack").unwrap();
+/// repl.eval("!!").unwrap();
+/// assert_eq!(repl.history().len(), 2);
+/// assert_eq!(repl.history()[1].0, "(hit 1D20 +5)");
+/// ```
+#[derive(Debug)]
+pub struct Repl {
+    dice: DiceGenerator,
+    definitions: Definitions,
+    history: Vec<(String, MultiTypeSession)>,
+}
+
+impl Repl {
+    pub fn new(dice: DiceGenerator) -> Repl {
+        Repl {
+            dice,
+            definitions: Definitions::new(),
+            history: vec![],
+        }
+    }
+
+    /// Past requests (with the macros replaced) and their rolls, from the oldest to the newest.
+    pub fn history(&self) -> &[(String, MultiTypeSession)] {
+        &self.history
+    }
+
+    /// Variables and macros defined so far.
+    pub fn definitions(&self) -> &Definitions {
+        &self.definitions
+    }
+
+    /// Evaluate one line of input and return the text to display.
+    pub fn eval(&mut self, line: &str) -> Result<String, Error> {
+        let line = line.trim();
+        if line.is_empty() {
+            Ok(String::new())
+        } else if line == HISTORY {
+            Ok(self.history_to_string())
+        } else if let Some(definition) = line.strip_prefix(DEFINE) {
+            self.define(definition)
+        } else if line == REPEAT {
+            let request = match self.history.last() {
+                Some((request, _)) => request.clone(),
+                None => {
+                    return Err(Error::new(ErrorKind::Parse(String::from(
+                        "No previous request to repeat",
+                    ))))
+                }
+            };
+            self.roll(request)
+        } else {
+            let request = self.definitions.resolve(line)?;
+            self.roll(request)
+        }
+    }
+
+    fn roll(&mut self, request: String) -> Result<String, Error> {
+        let session = parse_request_with_dice(&request, true, self.dice.clone())?;
+        let result = session.to_string();
+        self.history.push((request, session));
+        Ok(result)
+    }
+
+    /// Define a macro or a variable from the text following `:def`, like `attack = (hit 1D20 +5)`.
+    fn define(&mut self, definition: &str) -> Result<String, Error> {
+        let (name, text) = read_definition(definition.trim())?;
+        if name == HISTORY {
+            return Err(Error::new(ErrorKind::Parse(format!(
+                "{} is a command and can't be a macro name",
+                HISTORY
+            ))));
+        }
+        self.definitions.define(&name, &text);
+        Ok(format!("{} = {}", name, text))
+    }
+
+    fn history_to_string(&self) -> String {
+        self.history
+            .iter()
+            .enumerate()
+            .map(|(index, (request, session))| format!("#{} {}\n{}", index + 1, request, session))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dice::DiceGenerator;
+    use crate::errors::ErrorKind;
+    use crate::io::repl::*;
+
+    #[test]
+    fn repl_macros() {
+        let mut repl = Repl::new(DiceGenerator::from_seed(3));
+        assert_eq!(
+            repl.eval(":def attack = (hit 1D20 +5)").unwrap(),
+            "attack = (hit 1D20 +5)"
+        );
+        assert_eq!(
+            repl.eval(":def twice=attack attack").unwrap(),
+            "twice = attack attack"
+        );
+        assert_eq!(repl.eval(":def $str = 3").unwrap(), "$str = 3");
+        assert_eq!(repl.definitions().get("twice"), Some("attack attack"));
+        assert_eq!(repl.definitions().get("hit"), None);
+
+        repl.eval("twice +$str").unwrap();
+        assert_eq!(repl.history()[0].0, "(hit 1D20 +5) (hit 1D20 +5) +3");
+
+        // Definitions are resolved when they are used
+        repl.eval(":def attack = twice").unwrap();
+        match repl.eval("attack").unwrap_err().kind() {
+            ErrorKind::RecursiveDefinition(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+        match repl.eval("1D20 +$dex").unwrap_err().kind() {
+            ErrorKind::UndefinedName(_) => (),
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+
+        for invalid in [
+            ":def = 1D6",
+            ":def 2x = 1D6",
+            ":def a-b = 1D6",
+            ":def history = 1D6",
+            ":def x =",
+        ]
+        .iter()
+        {
+            match repl.eval(invalid).unwrap_err().kind() {
+                ErrorKind::Parse(_) | ErrorKind::Syntax(_) => (),
+                kind => panic!("Unexpected error {:?} for {}", kind, invalid),
+            }
+        }
+    }
+
+    #[test]
+    fn repl_history() {
+        let mut repl = Repl::new(DiceGenerator::from_seed(3));
+        assert!(repl.eval("!!").is_err());
+        assert_eq!(repl.eval("  ").unwrap(), "");
+        assert_eq!(repl.eval("history").unwrap(), "");
+
+        let first = repl.eval("3D6 +2").unwrap();
+        let second = repl.eval("!!").unwrap();
+        assert!(first.starts_with("Detailed rolls"));
+        assert!(second.starts_with("Detailed rolls"));
+        assert!(repl.eval("3D6 +").is_err());
+        assert_eq!(repl.history().len(), 2);
+        assert_eq!(repl.history()[1].0, "3D6 +2");
+        assert_eq!(
+            repl.eval("history").unwrap(),
+            format!("#1 3D6 +2\n{}\n#2 3D6 +2\n{}", first, second)
+        );
+
+        // The same seed gives the same rolls as outside of the interactive mode
+        let dice = DiceGenerator::from_seed(3);
+        let session = parse_request_with_dice("3D6 +2", true, dice.clone()).unwrap();
+        assert_eq!(session.to_string(), first);
+        let session = parse_request_with_dice("3D6 +2", true, dice).unwrap();
+        assert_eq!(session.to_string(), second);
+    }
+}
@@ -1,6 +1,12 @@
+use crate::actions::{Action, Aggregation, Comparison, RerollMode, RollCondition};
 use crate::dice::*;
-use crate::MultiTypeSession;
+use crate::errors::Error;
+use crate::io::read::{Definitions, FileRequest, RollFile};
+use crate::simulation::{Histogram, Simulation};
 use crate::TypedRollSession;
+use crate::{
+    EvaluatedExpression, Expression, MultiTypeRequest, MultiTypeSession, Operator, Rounding,
+};
 use std::fmt::{self, Display};
 
 impl Display for FudgeRoll {
@@ -17,14 +23,68 @@ impl Display for FudgeRoll {
     }
 }
 
+impl Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// The symbols of the face joined by `+`, like `Success+Advantage`, or `Blank`.
+impl Display for SymbolRoll {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbols: Vec<String> = self.symbols().map(|symbol| symbol.to_string()).collect();
+        if symbols.is_empty() {
+            write!(f, "Blank")
+        } else {
+            write!(f, "{}", symbols.join("+"))
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Comparison::GreaterOrEqual => ">=",
+                Comparison::LowerOrEqual => "<=",
+                Comparison::Equal => "==",
+                Comparison::Greater => ">",
+                Comparison::Lower => "<",
+            }
+        )
+    }
+}
+
+impl Display for RollCondition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RollCondition::Values(values) => write!(
+                f,
+                "{}",
+                values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            RollCondition::Threshold(comparison, target) => write!(f, "{}{}", comparison, target),
+            RollCondition::Range(low, high) => write!(f, "{}..{}", low, high),
+        }
+    }
+}
+
 impl fmt::Display for NumericDice {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
             "{}",
             match self {
-                NumericDice::ConstDice(const_value) => format!("+{}", const_value.to_string()),
+                NumericDice::ConstDice(const_value) => format!("{:+}", const_value),
                 NumericDice::NumberedDice(sides) => format!("D{}", sides),
+                NumericDice::CustomDice(faces) => format!("D{{{}}}", faces_to_string(faces)),
+                NumericDice::PercentileDice => String::from("D%"),
                 NumericDice::RepeatingDice(repeat_values) => format!(
                     "[{}...]",
                     repeat_values
@@ -58,13 +118,106 @@ impl fmt::Display for FudgeDice {
     }
 }
 
+impl fmt::Display for SymbolDice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SymbolDice::ConstDice(const_value) => write!(f, "{}", const_value),
+            SymbolDice::RepeatingDice(repeat_values) => write!(
+                f,
+                "[{}...]",
+                repeat_values
+                    .iter()
+                    .map(|val| val.to_string() + ",")
+                    .collect::<String>()
+            ),
+            dice => write!(f, "{:?}", dice),
+        }
+    }
+}
+
+impl Display for Critical {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Critical::Success => write!(f, "CRITICAL SUCCESS"),
+            Critical::Failure => write!(f, "CRITICAL FAILURE"),
+        }
+    }
+}
+
 impl<T: DiceBounds> fmt::Display for RollRequest<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let id = match &self.id {
             Some(ref id) => format!("{}: ", id),
             None => String::from(""),
         };
-        write!(f, "{}{}{}", id, self.number, self.dice.to_string())
+        write!(f, "{}{}{}", id, self.number, self.dice)
+    }
+}
+
+/// Render the history of the dice, like `~~1~~ 5, 6!, 2, ~~1~~, 6!+3, 20 (crit)`:
+/// rerolled values and dropped dice are struck through, exploding dice are followed by `!`,
+/// the rolls added by a compounding explosion are joined with `+`,
+/// and the critical natural rolls are followed by `(crit)` or `(fumble)`.
+///
+/// Return `None` if no die was rerolled, dropped, exploded or critical.
+pub fn audit_to_string<T: RollBounds>(history: &[DieHistory<T>]) -> Option<String> {
+    let untouched = history.iter().all(|die| {
+        die.values.len() == 1
+            && die.is_kept()
+            && die.exploded_from.is_none()
+            && die.compounded.is_empty()
+            && die.critical.is_none()
+    });
+    if untouched {
+        return None;
+    }
+    Some(
+        history
+            .iter()
+            .enumerate()
+            .map(|(index, die)| {
+                let mut values: Vec<String> = die
+                    .values
+                    .iter()
+                    .map(|value| format!("~~{}~~", value))
+                    .collect();
+                if die.is_kept() {
+                    values.pop();
+                    values.push(if die.compounded.is_empty() {
+                        die.value().to_string()
+                    } else {
+                        die.compounded
+                            .iter()
+                            .map(|roll| roll.to_string())
+                            .collect::<Vec<String>>()
+                            .join("!+")
+                    });
+                }
+                let exploded = history
+                    .iter()
+                    .any(|other| other.exploded_from == Some(index));
+                let critical = match die.critical {
+                    Some(Critical::Success) => " (crit)",
+                    Some(Critical::Failure) => " (fumble)",
+                    None => "",
+                };
+                format!(
+                    "{}{}{}",
+                    values.join(" "),
+                    if exploded { "!" } else { "" },
+                    critical
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", "),
+    )
+}
+
+/// Description of the rolls, followed by the history of the dice if anything happened to them.
+pub(crate) fn describe_with_audit<T: RollBounds, V: DiceBounds>(rolls: &Rolls<T, V>) -> String {
+    match audit_to_string(&rolls.history) {
+        Some(audit) => format!("{} [{}]", rolls.description, audit),
+        None => rolls.description.clone(),
     }
 }
 
@@ -73,7 +226,7 @@ impl<T: RollBounds, V: DiceBounds> fmt::Display for Rolls<T, V> {
         write!(
             f,
             "{}: {}",
-            self.description,
+            describe_with_audit(self),
             self.rolls
                 .iter()
                 .map(|roll| roll.to_string())
@@ -83,18 +236,54 @@ impl<T: RollBounds, V: DiceBounds> fmt::Display for Rolls<T, V> {
     }
 }
 
-impl<T: RollBounds, V: DiceBounds> ToString for TypedRollSession<T, V> {
-    fn to_string(&self) -> String {
-        self.rolls
-            .iter()
-            .map(|roll| roll.to_string())
-            .collect::<Vec<String>>()
-            .join("\n")
+impl<T: RollBounds, V: DiceBounds> fmt::Display for TypedRollSession<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.rolls
+                .iter()
+                .map(|roll| roll.to_string())
+                .collect::<Vec<String>>()
+                .join("\n")
+        )
     }
 }
 
-impl ToString for MultiTypeSession {
-    fn to_string(&self) -> String {
+/// Label of a result on the Fate ladder, like `Great (+4)`.
+///
+/// The results beyond the ends of the ladder keep the label of the end.
+/// # Example
+/// ```
+/// # use letsroll::io::write::fate_ladder;
+/// assert_eq!(fate_ladder(4), "Great (+4)");
+/// assert_eq!(fate_ladder(0), "Mediocre (+0)");
+/// assert_eq!(fate_ladder(-3), "Terrible (-3)");
+/// assert_eq!(fate_ladder(10), "Legendary (+10)");
+/// ```
+pub fn fate_ladder(value: i64) -> String {
+    const LADDER: [&str; 11] = [
+        "Terrible",
+        "Poor",
+        "Mediocre",
+        "Average",
+        "Fair",
+        "Good",
+        "Great",
+        "Superb",
+        "Fantastic",
+        "Epic",
+        "Legendary",
+    ];
+    format!(
+        "{} ({:+})",
+        LADDER[(value.clamp(-2, 8) + 2) as usize],
+        value
+    )
+}
+
+impl fmt::Display for MultiTypeSession {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut subresults: Vec<String> = vec![];
         if let Some(session) = &self.numeric_session {
             subresults.push(session.to_string());
@@ -102,7 +291,471 @@ impl ToString for MultiTypeSession {
         if let Some(session) = &self.fudge_session {
             subresults.push(session.to_string());
         }
-        subresults.join("\n")
+        if let Some(session) = &self.symbol_session {
+            subresults.push(session.to_string());
+        }
+        if let Some(results) = &self.symbol_results {
+            subresults.push(results.to_string());
+        }
+        if let Some(critical) = self.critical {
+            subresults.push(critical.to_string());
+        }
+        if let (true, Some(total)) = (self.fudge_totaled, self.total()) {
+            subresults.push(format!("FATE LADDER \t: {}", fate_ladder(i64::from(total))));
+        }
+        write!(f, "{}", subresults.join("\n"))
+    }
+}
+
+/// One definition per line, in the syntax read by [read_document](../read/fn.read_document.html).
+impl fmt::Display for Definitions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.iter()
+                .map(|(name, text)| format!("{} = {}", name, text))
+                .collect::<Vec<String>>()
+                .join("\n")
+        )
+    }
+}
+
+impl fmt::Display for FileRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "{}: {}", label, self.request),
+            None => write!(f, "{}", self.request),
+        }
+    }
+}
+
+/// The definitions, then one request per line: the includes are replaced by the content of the included files.
+impl fmt::Display for RollFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut lines: Vec<String> = vec![];
+        if !self.definitions().is_empty() {
+            lines.push(self.definitions().to_string());
+        }
+        lines.extend(self.requests().iter().map(|request| request.to_string()));
+        writeln!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl fmt::Display for Rounding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rounding::Up => write!(f, "up"),
+            Rounding::Down => write!(f, "down"),
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operator::Add => write!(f, "+"),
+            Operator::Subtract => write!(f, "-"),
+            Operator::Multiply => write!(f, "x"),
+            Operator::Divide(_) => write!(f, "/"),
+        }
+    }
+}
+
+/// Operands show their own value, the value of the whole expression is left to the caller
+impl fmt::Display for EvaluatedExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvaluatedExpression::Operation(operator, left, right, _) => {
+                write!(
+                    f,
+                    "{} {} {}",
+                    operand_to_string(left),
+                    operator,
+                    operand_to_string(right)
+                )?;
+                if let Operator::Divide(rounding) = operator {
+                    write!(f, " {}", rounding)?;
+                }
+                Ok(())
+            }
+            EvaluatedExpression::Dice(description, value) => write!(f, "{}:{}", description, value),
+            EvaluatedExpression::Constant(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+fn operand_to_string(operand: &EvaluatedExpression) -> String {
+    match operand {
+        EvaluatedExpression::Constant(value) => value.to_string(),
+        EvaluatedExpression::Dice(..) => format!("({})", operand),
+        EvaluatedExpression::Operation(.., value) => format!("({} = {})", operand, value),
+    }
+}
+
+/// Conversion back into the request syntax.
+///
+/// The canonical string is read by [read_request](../read/fn.read_request.html)
+/// into a structure equal to the original one. Every request read by `read_request`
+/// can be written: only the structures built by hand may have no request syntax.
+///
+/// # Example
+/// ```
+/// # use letsroll::io::read::read_request;
+/// # use letsroll::io::write::ToRequestString;
+/// let request = read_request("4d6kh3 (FIRE 2d8r1) + 2").unwrap();
+/// let canonical = request.to_request_string().unwrap();
+/// assert_eq!(canonical, "(4D6 KeepBest(3)) (FIRE 2D8 Reroll(1)) +2");
+/// assert_eq!(read_request(&canonical).unwrap(), request);
+/// ```
+pub trait ToRequestString {
+    fn to_request_string(&self) -> Result<String, Error>;
+}
+
+impl ToRequestString for Action {
+    fn to_request_string(&self) -> Result<String, Error> {
+        match self {
+            Action::RerollNumeric(condition, mode) => Ok(format!(
+                "{}({})",
+                reroll_keyword(*mode),
+                condition_string(condition)?
+            )),
+            Action::RerollFudge(values, mode) => Ok(format!(
+                "{}({})",
+                reroll_keyword(*mode),
+                fudge_values(values)?
+            )),
+            Action::Explode(condition) => Ok(format!("Explode({})", condition_string(condition)?)),
+            Action::Compound(condition) => {
+                Ok(format!("Compound({})", condition_string(condition)?))
+            }
+            Action::Penetrate(condition) => {
+                Ok(format!("Penetrate({})", condition_string(condition)?))
+            }
+            Action::ExplodeFudge(values) => Ok(format!("Explode({})", fudge_values(values)?)),
+            Action::Total => Ok(String::from("Total")),
+            Action::Concat => Ok(String::from("Concat")),
+            Action::FlipFlop => Ok(String::from("Flip")),
+            Action::MultiplyBy(factor) => Ok(format!("x{}", positive_values(&[*factor])?)),
+            Action::KeepBest(n) => Ok(format!("KeepBest({})", positive_number(*n)?)),
+            Action::KeepWorst(n) => Ok(format!("KeepWorst({})", positive_number(*n)?)),
+            Action::RerollBest(n) => Ok(format!("RerollBest({})", positive_number(*n)?)),
+            Action::RerollWorst(n) => Ok(format!("RerollWorst({})", positive_number(*n)?)),
+            Action::CountSuccesses(comparison, target, botch) => {
+                let mut action =
+                    format!("Successes({}{})", comparison, positive_values(&[*target])?);
+                if !botch.is_empty() {
+                    action += &format!(" Botch({})", positive_values(botch)?);
+                }
+                Ok(action)
+            }
+            Action::Advantage => Ok(String::from("Advantage")),
+            Action::Disadvantage => Ok(String::from("Disadvantage")),
+            Action::Critical(None) => Ok(String::from("Crit")),
+            Action::Critical(Some(condition)) => {
+                Ok(format!("Crit({})", condition_string(condition)?))
+            }
+            Action::CriticalDamage => Ok(String::from("CritDamage")),
+            Action::Bonus(n) => Ok(format!("Bonus({})", positive_number(*n)?)),
+            Action::Penalty(n) => Ok(format!("Penalty({})", positive_number(*n)?)),
+            Action::SkillCheck(skill) => Ok(format!("Skill({})", positive_values(&[*skill])?)),
+            // "Sum" is read as the Total action, and the negation is only a dice sign
+            Action::Sum | Action::Negate => Err(Error::not_writable(&self.to_string())),
+        }
+    }
+}
+
+impl ToRequestString for Aggregation {
+    fn to_request_string(&self) -> Result<String, Error> {
+        match self {
+            Aggregation::CountValues => Ok(String::from("Count")),
+            Aggregation::SumById => Ok(String::from("SumById")),
+            Aggregation::CancelSymbols => Ok(String::from("Cancel")),
+        }
+    }
+}
+
+impl ToRequestString for NumericRollRequest {
+    fn to_request_string(&self) -> Result<String, Error> {
+        let dice = match self.dice {
+            NumericDice::NumberedDice(sides) if sides > 0 && self.number > 0 => {
+                format!("{}D{}", self.number, sides)
+            }
+            NumericDice::CustomDice(ref faces) if !faces.is_empty() && self.number > 0 => {
+                format!("{}D{{{}}}", self.number, faces_to_string(faces))
+            }
+            NumericDice::PercentileDice if self.number > 0 => format!("{}D%", self.number),
+            NumericDice::ConstDice(value) if value != 0 && self.number == 1 => {
+                format!("{:+}", value)
+            }
+            _ => return Err(Error::not_writable(&self.to_string())),
+        };
+        dice_group_to_string(dice, &self.id, &self.actions)
+    }
+}
+
+impl ToRequestString for FudgeRollRequest {
+    fn to_request_string(&self) -> Result<String, Error> {
+        let dice = match self.dice {
+            FudgeDice::FudgeDice if self.number > 0 => format!("{}F", self.number),
+            _ => return Err(Error::not_writable(&self.to_string())),
+        };
+        dice_group_to_string(dice, &self.id, &self.actions)
+    }
+}
+
+impl ToRequestString for SymbolRollRequest {
+    fn to_request_string(&self) -> Result<String, Error> {
+        let dice = match self.dice {
+            SymbolDice::ConstDice(_) | SymbolDice::RepeatingDice(_) => {
+                return Err(Error::not_writable(&self.to_string()))
+            }
+            _ if self.number == 0 => return Err(Error::not_writable(&self.to_string())),
+            _ => format!("{}{}", self.number, self.dice),
+        };
+        dice_group_to_string(dice, &self.id, &self.actions)
+    }
+}
+
+impl ToRequestString for MultiTypeRequest {
+    fn to_request_string(&self) -> Result<String, Error> {
+        if let Some(expression) = &self.expression {
+            if !self.actions.is_empty() || self.aggregation.is_some() {
+                return Err(Error::not_writable(
+                    "An arithmetic expression with global actions or aggregation",
+                ));
+            }
+            // Without the mark, an expression could be read as a list of dice
+            return Ok(format!("= {}", expression_to_string(expression, self)?));
+        }
+
+        let mut groups: Vec<String> = vec![];
+        for request in self.numeric_requests.iter() {
+            groups.push(request.to_request_string()?);
+        }
+        for request in self.fudge_requests.iter() {
+            groups.push(request.to_request_string()?);
+        }
+        for request in self.symbol_requests.iter() {
+            groups.push(request.to_request_string()?);
+        }
+        for action in self.actions.iter() {
+            groups.push(action.to_request_string()?);
+        }
+        if let Some(aggregation) = &self.aggregation {
+            groups.push(aggregation.to_request_string()?);
+        }
+        Ok(groups.join(" "))
+    }
+}
+
+/// Write a dice with its optional ID and actions, a final negation being written as a sign.
+fn dice_group_to_string(
+    dice: String,
+    id: &Option<DiceID>,
+    actions: &[Action],
+) -> Result<String, Error> {
+    let (sign, actions) = match actions.split_last() {
+        Some((Action::Negate, actions)) => ("-", actions),
+        _ => ("", actions),
+    };
+    if id.is_none() && actions.is_empty() {
+        return Ok(format!("{}{}", sign, dice));
+    }
+    let mut group = vec![dice];
+    if let Some(id) = id {
+        group.insert(0, id.clone());
+    }
+    for action in actions.iter() {
+        group.push(action.to_request_string()?);
+    }
+    Ok(format!("{}({})", sign, group.join(" ")))
+}
+
+fn expression_to_string(
+    expression: &Expression,
+    requests: &MultiTypeRequest,
+) -> Result<String, Error> {
+    match expression {
+        Expression::Fudge(index) => {
+            let request = &requests.fudge_requests[*index];
+            if let Some(Action::Negate) = request.actions.last() {
+                return Err(Error::not_writable(
+                    "A subtracted dice in an arithmetic expression",
+                ));
+            }
+            request.to_request_string()
+        }
+        Expression::Dice(index) => {
+            let request = &requests.numeric_requests[*index];
+            if let Some(Action::Negate) = request.actions.last() {
+                return Err(Error::not_writable(
+                    "A subtracted dice in an arithmetic expression",
+                ));
+            }
+            let dice = request.to_request_string()?;
+            // Only the numbered dice can be written without parenthesis
+            match request.dice {
+                NumericDice::ConstDice(_) if !dice.starts_with('(') => Ok(format!("({})", dice)),
+                _ => Ok(dice),
+            }
+        }
+        Expression::Constant(value) if *value > 0 => Ok(value.to_string()),
+        Expression::Constant(value) => Err(Error::not_writable(&value.to_string())),
+        Expression::Operation(operator, left, right) => {
+            // Operations are read from left to right, the multiplications and divisions first
+            let level = precedence(expression);
+            let mut left_operand = expression_to_string(left, requests)?;
+            if precedence(left) < level {
+                left_operand = format!("({})", left_operand);
+            }
+            let mut right_operand = expression_to_string(right, requests)?;
+            if precedence(right) <= level {
+                right_operand = format!("({})", right_operand);
+            }
+            match operator {
+                Operator::Divide(rounding) => {
+                    Ok(format!("{} / {} {}", left_operand, right_operand, rounding))
+                }
+                _ => Ok(format!("{} {} {}", left_operand, operator, right_operand)),
+            }
+        }
+    }
+}
+
+fn precedence(expression: &Expression) -> u8 {
+    match expression {
+        Expression::Operation(Operator::Add, ..)
+        | Expression::Operation(Operator::Subtract, ..) => 1,
+        Expression::Operation(..) => 2,
+        _ => 3,
+    }
+}
+
+fn faces_to_string(faces: &[NumericRoll]) -> String {
+    faces
+        .iter()
+        .map(|face| face.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn positive_values(values: &[NumericRoll]) -> Result<String, Error> {
+    if values.is_empty() || values.iter().any(|value| *value <= 0) {
+        return Err(Error::not_writable(&format!("{:?}", values)));
+    }
+    Ok(values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(","))
+}
+
+fn condition_string(condition: &RollCondition) -> Result<String, Error> {
+    match condition {
+        RollCondition::Values(values) => positive_values(values),
+        RollCondition::Threshold(comparison, target) => {
+            Ok(format!("{}{}", comparison, positive_values(&[*target])?))
+        }
+        RollCondition::Range(low, high) if low <= high => Ok(format!(
+            "{}..{}",
+            positive_values(&[*low])?,
+            positive_values(&[*high])?
+        )),
+        RollCondition::Range(_, _) => Err(Error::not_writable(&condition.to_string())),
+    }
+}
+
+fn reroll_keyword(mode: RerollMode) -> &'static str {
+    match mode {
+        RerollMode::Once => "Reroll",
+        RerollMode::Until => "RerollUntil",
+    }
+}
+
+fn fudge_values(values: &[FudgeRoll]) -> Result<String, Error> {
+    if values.is_empty() {
+        return Err(Error::not_writable("[]"));
+    }
+    Ok(values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(","))
+}
+
+fn positive_number(number: DiceNumber) -> Result<DiceNumber, Error> {
+    match number {
+        0 => Err(Error::not_writable("0")),
+        _ => Ok(number),
+    }
+}
+
+/// Maximum width of the bars of an histogram
+const HISTOGRAM_WIDTH: u64 = 50;
+
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = self.total();
+        let max_count = *self.counts().values().max().unwrap_or(&0);
+        let width = self
+            .counts()
+            .keys()
+            .map(|value| value.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<String> = self
+            .counts()
+            .iter()
+            .map(|(value, count)| {
+                format!(
+                    "{:>width$} | {:<bar_width$} {:.1}%",
+                    value,
+                    "#".repeat((count * HISTOGRAM_WIDTH / max_count) as usize),
+                    *count as f64 * 100.0 / total as f64,
+                    width = width,
+                    bar_width = HISTOGRAM_WIDTH as usize
+                )
+            })
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+fn histogram_statistics(histogram: &Histogram) -> String {
+    match histogram.mean() {
+        None => String::from("no result"),
+        Some(mean) => format!(
+            "mean {:.2}, P10 {}, P25 {}, P50 {}, P75 {}, P90 {}",
+            mean,
+            histogram.percentile(10.0).unwrap(),
+            histogram.percentile(25.0).unwrap(),
+            histogram.percentile(50.0).unwrap(),
+            histogram.percentile(75.0).unwrap(),
+            histogram.percentile(90.0).unwrap()
+        ),
+    }
+}
+
+impl fmt::Display for Simulation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut subresults: Vec<String> = vec![format!("Simulation of {} rolls", self.runs())];
+        if self.rolls().total() > 0 {
+            subresults.push(format!(
+                "RESULTS\t: {}\n{}",
+                histogram_statistics(self.rolls()),
+                self.rolls()
+            ));
+        }
+        for (description, histogram) in self.counts().iter() {
+            subresults.push(format!(
+                "{}\t: {}\n{}",
+                description,
+                histogram_statistics(histogram),
+                histogram
+            ));
+        }
+        write!(f, "{}", subresults.join("\n"))
     }
 }
 
@@ -110,13 +763,23 @@ impl ToString for MultiTypeSession {
 mod tests {
 
     use crate::actions::Action;
+    use crate::actions::{
+        Aggregation, Comparison, Compound, DetectCritical, Explode, KeepBest, Penetrate, Reroll,
+        RerollMode, RollCondition,
+    };
     use crate::dice::*;
+    use crate::io::read::read_request;
+    use crate::io::write::{audit_to_string, ToRequestString};
+    use crate::simulation::Histogram;
+    use crate::{EvaluatedExpression, Expression, MultiTypeRequest, Operator, Rounding};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
     #[test]
     fn numeric_roll_to_string() {
         let dice = DiceGenerator::new();
         assert_eq!(
-            dice.roll(1, &NumericDice::ConstDice(20))[0].to_string(),
+            dice.roll(1, &NumericDice::ConstDice(20)).unwrap()[0].to_string(),
             "20"
         );
     }
@@ -125,15 +788,21 @@ mod tests {
     fn fudge_roll_to_string() {
         let dice = DiceGenerator::new();
         assert_eq!(
-            dice.roll(1, &FudgeDice::ConstDice(FudgeRoll::Blank))[0].to_string(),
+            dice.roll(1, &FudgeDice::ConstDice(FudgeRoll::Blank))
+                .unwrap()[0]
+                .to_string(),
             "0"
         );
         assert_eq!(
-            dice.roll(1, &FudgeDice::ConstDice(FudgeRoll::Minus))[0].to_string(),
+            dice.roll(1, &FudgeDice::ConstDice(FudgeRoll::Minus))
+                .unwrap()[0]
+                .to_string(),
             "-"
         );
         assert_eq!(
-            dice.roll(1, &FudgeDice::ConstDice(FudgeRoll::Plus))[0].to_string(),
+            dice.roll(1, &FudgeDice::ConstDice(FudgeRoll::Plus))
+                .unwrap()[0]
+                .to_string(),
             "+"
         );
     }
@@ -141,6 +810,25 @@ mod tests {
     #[test]
     fn numbered_dice_to_string() {
         assert_eq!(NumericDice::NumberedDice(20).to_string(), "D20");
+        assert_eq!(
+            NumericDice::CustomDice(vec![-1, 0, 0, 1]).to_string(),
+            "D{-1,0,0,1}"
+        );
+        assert_eq!(
+            RollRequest::new(2, NumericDice::CustomDice(vec![0, 1]))
+                .to_request_string()
+                .unwrap(),
+            "2D{0,1}"
+        );
+        assert_eq!(NumericDice::PercentileDice.to_string(), "D%");
+        assert_eq!(
+            RollRequest::new(1, NumericDice::PercentileDice)
+                .add_action(Action::Bonus(2))
+                .add_action(Action::SkillCheck(60))
+                .to_request_string()
+                .unwrap(),
+            "(1D% Bonus(2) Skill(60))"
+        );
     }
 
     #[test]
@@ -148,9 +836,36 @@ mod tests {
         assert_eq!(FudgeDice::FudgeDice.to_string(), "F");
     }
 
+    #[test]
+    fn symbol_dice_to_string() {
+        assert_eq!(SymbolDice::Proficiency.to_string(), "Proficiency");
+        assert_eq!(
+            RollRequest::new(2, SymbolDice::Setback)
+                .add_id(Some(String::from("fear")))
+                .to_request_string()
+                .unwrap(),
+            "(fear 2Setback)"
+        );
+        assert_eq!(SymbolRoll::BLANK.to_string(), "Blank");
+        assert_eq!(
+            SymbolRoll::double(Symbol::Success, Symbol::Advantage).to_string(),
+            "Success+Advantage"
+        );
+        let rolls = SymbolRolls::new(
+            RollRequest::new(
+                2,
+                SymbolDice::ConstDice(SymbolRoll::single(Symbol::Triumph)),
+            ),
+            &DiceGenerator::new(),
+        )
+        .unwrap();
+        assert_eq!(rolls.to_string(), "2Triumph: Triumph Triumph");
+    }
+
     #[test]
     fn const_dice_to_string() {
         assert_eq!(NumericDice::ConstDice(42).to_string(), "+42");
+        assert_eq!(NumericDice::ConstDice(-42).to_string(), "-42");
     }
 
     #[test]
@@ -177,4 +892,384 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rolls_audit_to_string() {
+        let input = vec![3, 6, 1];
+        let dice_request = NumericRollRequest::new(3, NumericDice::RepeatingDice(input));
+        let dice = DiceGenerator::new();
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        assert_eq!(audit_to_string(&rolls.history), None);
+        assert_eq!(rolls.to_string(), "3[3,6,1,...]: 3 6 1");
+
+        let rolls = rolls.reroll(&dice, &[1]).unwrap();
+        let rolls = rolls.explode(&dice, &[6]).unwrap();
+        let rolls = rolls.keep_best(3).unwrap();
+        assert_eq!(
+            audit_to_string(&rolls.history),
+            Some(String::from("~~3~~, 6!, ~~1~~ 3, 3"))
+        );
+        assert_eq!(
+            rolls.to_string(),
+            "3[3,6,1,...] Reroll(1) explode(6) KeepBest(3) [~~3~~, 6!, ~~1~~ 3, 3]: 3 3 6"
+        );
+
+        let dice_request = NumericRollRequest::new(2, NumericDice::RepeatingDice(vec![2, 6]));
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        let condition = RollCondition::Threshold(Comparison::GreaterOrEqual, 6);
+        assert_eq!(
+            rolls.compound(&dice, &condition).unwrap().to_string(),
+            "2[2,6,...] compound(>=6) [2, 6!+2]: 2 8"
+        );
+        assert_eq!(
+            rolls.penetrate(&dice, &condition).unwrap().to_string(),
+            "2[2,6,...] penetrate(>=6) [2, 6!, 1]: 2 6 1"
+        );
+
+        let dice_request = NumericRollRequest::new(3, NumericDice::RepeatingDice(vec![1, 4, 6]));
+        let rolls = NumericRolls::new(dice_request, &dice).unwrap();
+        assert_eq!(
+            rolls.detect_critical(None).to_string(),
+            "3[1,4,6,...] Crit [1 (fumble), 4, 6 (crit)]: 1 4 6"
+        );
+    }
+
+    #[test]
+    fn expression_to_string() {
+        let expression = EvaluatedExpression::Operation(
+            Operator::Divide(Rounding::Up),
+            Box::new(EvaluatedExpression::Operation(
+                Operator::Add,
+                Box::new(EvaluatedExpression::Dice(String::from("2D6"), 7)),
+                Box::new(EvaluatedExpression::Constant(3)),
+                10,
+            )),
+            Box::new(EvaluatedExpression::Constant(4)),
+            3,
+        );
+        assert_eq!(expression.to_string(), "((2D6:7) + 3 = 10) / 4 up");
+    }
+
+    #[test]
+    fn request_to_request_string() {
+        let canonical = |request: &str| read_request(request).unwrap().to_request_string().unwrap();
+        assert_eq!(canonical("d20"), "1D20");
+        assert_eq!(
+            canonical("(FIRE 10d12 KeepBest(1)) 4F Reroll(-) SumById"),
+            "(FIRE 10D12 KeepBest(1)) 4F Reroll(-) SumById"
+        );
+        assert_eq!(
+            canonical("2D6 - 1D4 -(ICE 3D6 x2) - 3 Successes(>=5) Botch(1,2)"),
+            "2D6 -1D4 -(ICE 3D6 x2) -3 Successes(>=5) Botch(1,2)"
+        );
+        assert_eq!(
+            canonical("3d6! 8d10>=7"),
+            "(3D6 Explode(6)) (8D10 Successes(>=7))"
+        );
+        assert_eq!(
+            canonical("((2D6 + 3) * 2 - (+4)) / (1D4 - 1D2 x 2) up"),
+            "= ((2D6 + 3) x 2 - (+4)) / (1D4 - 1D2 x 2) up"
+        );
+        assert_eq!(canonical("1D6 - (2D6 - 3)"), "= 1D6 - (2D6 - 3)");
+        assert_eq!(canonical("= 2D6 x 2 + 1"), "= 2D6 x 2 + 1");
+        assert_eq!(canonical("2D6 +1 x2"), "2D6 +1 x2");
+
+        let not_writable = |request: MultiTypeRequest| request.to_request_string().unwrap_err();
+        not_writable(MultiTypeRequest {
+            numeric_requests: vec![RollRequest::new(1, NumericDice::RepeatingDice(vec![1]))],
+            ..Default::default()
+        });
+        not_writable(MultiTypeRequest {
+            numeric_requests: vec![RollRequest::new(2, NumericDice::NumberedDice(6))],
+            actions: vec![Action::Sum],
+            ..Default::default()
+        });
+    }
+
+    fn random_values(rng: &mut StdRng) -> Vec<NumericRoll> {
+        (0..rng.gen_range(1, 4))
+            .map(|_| rng.gen_range(1, 21))
+            .collect()
+    }
+
+    fn random_action(rng: &mut StdRng) -> Action {
+        let fudge_values = [FudgeRoll::Plus, FudgeRoll::Minus, FudgeRoll::Blank];
+        match rng.gen_range(0, 25) {
+            0 => Action::RerollNumeric(RollCondition::Values(random_values(rng)), RerollMode::Once),
+            1 => Action::RerollFudge(
+                fudge_values[..rng.gen_range(1, 4)].to_vec(),
+                RerollMode::Until,
+            ),
+            16 => Action::RerollNumeric(
+                RollCondition::Range(rng.gen_range(1, 4), rng.gen_range(4, 8)),
+                RerollMode::Until,
+            ),
+            17 => Action::Explode(RollCondition::Threshold(
+                Comparison::Greater,
+                rng.gen_range(1, 21),
+            )),
+            18 => Action::Advantage,
+            19 => Action::Disadvantage,
+            20 => Action::Critical(None),
+            21 => Action::CriticalDamage,
+            22 => Action::Bonus(rng.gen_range(1, 4)),
+            23 => Action::Penalty(rng.gen_range(1, 4)),
+            24 => Action::SkillCheck(rng.gen_range(1, 100)),
+            2 => Action::Explode(RollCondition::Values(random_values(rng))),
+            14 => Action::Compound(RollCondition::Threshold(
+                Comparison::GreaterOrEqual,
+                rng.gen_range(1, 21),
+            )),
+            15 => Action::Penetrate(RollCondition::Values(random_values(rng))),
+            3 => Action::ExplodeFudge(fudge_values[rng.gen_range(0, 3)..].to_vec()),
+            4 => Action::Total,
+            5 => Action::Concat,
+            6 => Action::FlipFlop,
+            7 => Action::MultiplyBy(rng.gen_range(1, 10)),
+            8 => Action::KeepBest(rng.gen_range(1, 10)),
+            9 => Action::KeepWorst(rng.gen_range(1, 10)),
+            10 => Action::RerollBest(rng.gen_range(1, 10)),
+            11 => Action::RerollWorst(rng.gen_range(1, 10)),
+            12 => Action::CountSuccesses(Comparison::LowerOrEqual, rng.gen_range(1, 10), vec![]),
+            _ => Action::CountSuccesses(Comparison::Equal, 6, random_values(rng)),
+        }
+    }
+
+    fn random_group<T: DiceBounds>(
+        rng: &mut StdRng,
+        request: RollRequest<T>,
+        negate: bool,
+    ) -> RollRequest<T> {
+        let ids = [
+            None,
+            Some(String::from("FIRE")),
+            Some(String::from("ice_2")),
+        ];
+        let mut request = request.add_id(ids[rng.gen_range(0, 3)].clone());
+        for _ in 0..rng.gen_range(0, 3) {
+            request = request.add_action(random_action(rng));
+        }
+        if negate && rng.gen() {
+            request = request.add_action(Action::Negate);
+        }
+        request
+    }
+
+    fn random_numeric_group(rng: &mut StdRng, negate: bool) -> NumericRollRequest {
+        let request = match rng.gen_range(0, 6) {
+            5 => RollRequest::new(rng.gen_range(1, 20), NumericDice::PercentileDice),
+            0 | 1 => RollRequest::new(
+                rng.gen_range(1, 20),
+                NumericDice::NumberedDice(rng.gen_range(1, 101)),
+            ),
+            2 => {
+                let faces = (0..rng.gen_range(1, 7))
+                    .map(|_| rng.gen_range(-3, 10))
+                    .collect();
+                RollRequest::new(rng.gen_range(1, 20), NumericDice::CustomDice(faces))
+            }
+            _ => {
+                let value = rng.gen_range(1, 50);
+                RollRequest::new(
+                    1,
+                    NumericDice::ConstDice(if rng.gen() { value } else { -value }),
+                )
+            }
+        };
+        random_group(rng, request, negate)
+    }
+
+    fn random_expression(
+        rng: &mut StdRng,
+        request: &mut MultiTypeRequest,
+        depth: u8,
+    ) -> Expression {
+        match rng.gen_range(0, 5) {
+            _ if depth == 0 => Expression::Constant(rng.gen_range(1, 10)),
+            0 => {
+                let numeric_dice = random_numeric_group(rng, false);
+                request.numeric_requests.push(numeric_dice);
+                Expression::Dice(request.numeric_requests.len() - 1)
+            }
+            1 => {
+                let fudge_dice = RollRequest::new(rng.gen_range(1, 20), FudgeDice::FudgeDice);
+                let fudge_dice = random_group(rng, fudge_dice, false);
+                request.fudge_requests.push(fudge_dice);
+                Expression::Fudge(request.fudge_requests.len() - 1)
+            }
+            2 => Expression::Constant(rng.gen_range(1, 10)),
+            _ => random_operation(rng, request, depth - 1),
+        }
+    }
+
+    fn random_operation(rng: &mut StdRng, request: &mut MultiTypeRequest, depth: u8) -> Expression {
+        let operators = [
+            Operator::Add,
+            Operator::Subtract,
+            Operator::Multiply,
+            Operator::Divide(Rounding::Up),
+            Operator::Divide(Rounding::Down),
+        ];
+        let operator = operators[rng.gen_range(0, 5)];
+        let left = random_expression(rng, request, depth);
+        let right = random_expression(rng, request, depth);
+        Expression::Operation(operator, Box::new(left), Box::new(right))
+    }
+
+    #[test]
+    fn request_string_round_trip() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..500 {
+            let mut request = MultiTypeRequest::default();
+            for _ in 0..rng.gen_range(0, 4) {
+                request
+                    .numeric_requests
+                    .push(random_numeric_group(&mut rng, true));
+            }
+            for _ in 0..rng.gen_range(0, 3) {
+                let fudge_dice = RollRequest::new(rng.gen_range(1, 20), FudgeDice::FudgeDice);
+                request
+                    .fudge_requests
+                    .push(random_group(&mut rng, fudge_dice, true));
+            }
+            for _ in 0..rng.gen_range(0, 2) {
+                let symbol_dice = [
+                    SymbolDice::Boost,
+                    SymbolDice::Setback,
+                    SymbolDice::Ability,
+                    SymbolDice::Difficulty,
+                    SymbolDice::Proficiency,
+                    SymbolDice::Challenge,
+                ][rng.gen_range(0, 6)]
+                .clone();
+                let symbol_dice = RollRequest::new(rng.gen_range(1, 20), symbol_dice);
+                request
+                    .symbol_requests
+                    .push(random_group(&mut rng, symbol_dice, true));
+            }
+            if request.numeric_requests.is_empty()
+                && request.fudge_requests.is_empty()
+                && request.symbol_requests.is_empty()
+            {
+                continue;
+            }
+            for _ in 0..rng.gen_range(0, 3) {
+                request.actions.push(random_action(&mut rng));
+            }
+            request.aggregation = match rng.gen_range(0, 4) {
+                0 => Some(Aggregation::CountValues),
+                1 => Some(Aggregation::SumById),
+                2 => Some(Aggregation::CancelSymbols),
+                _ => None,
+            };
+            let canonical = request.to_request_string().unwrap();
+            assert_eq!(read_request(&canonical).unwrap(), request, "{}", canonical);
+        }
+    }
+
+    #[test]
+    fn read_request_string_round_trip() {
+        // The requests of the parser tests
+        let requests = [
+            "10D20 (DMG 4D6 KeepBest(3)) 5F",
+            "10D{2,3,3,4,4,5}",
+            "3D{0,0,1,1,2} 1d{-1,0,1}!",
+            "(ABC_4A 10F)",
+            "1D20 -2 - 3",
+            "2D6 - 1D4 -(FIRE 4D6 KeepBest(3)) +1D8",
+            "+2 -5 -(+4)",
+            "1D6 - 4F",
+            "1D6 -4F",
+            "(FIRE +5)",
+            "(FIRE 2D1) (FORCE +4) (FIRE +3) SumById",
+            "(FIRE 4F) SumById",
+            "(FIRE 4d6r1kh3 x2) 2d20kl1 + 5",
+            "(HIT 1D20 Crit) (HIT +5) (DMG 1D8 CritDamage) +3",
+            "(1D20 Crit(19,20))",
+            "(8D10 Successes(>=8) Botch(1)) 5D6 Successes(=6)",
+            "3D6 Successes(<=2) Botch(5,6)",
+            "3D6 Successes(==2)",
+            "3D6 Successes(>5)",
+            "8d10>=7",
+            "d10=7",
+            "4d6kh3",
+            "4d6k3",
+            "2d20kl1",
+            "4d6r1r2",
+            "1d20r<3",
+            "1d20r1..2",
+            "1D20 RerollUntil(1..3)",
+            "4F Reroll(-)",
+            "4F RerollUntil(-)",
+            "3d6!",
+            "1d10!!",
+            "1d6!p",
+            "(1D10 Explode(==9))",
+            "1D10 Explode(>=9)",
+            "1d20adv +5",
+            "1d20 adv +5",
+            "1d20dis",
+            "(1D20 Advantage) +5",
+            "(D% Bonus) (luck 1d% Penalty(2) Skill(45))",
+            "50D%",
+            "d%!",
+            "2Ability (fear 1setback) Proficiency Cancel",
+            "1D20 + 3 x2",
+            "2D6 +3 x2",
+            "2D6 Sum",
+            "2D6 Concat Flip Count",
+            "(2D6 + 3) x 2",
+            "(2D1 + 3) / 4 up x 3",
+            "(2D1 - 5) / 2 DOWN",
+            "(2F + 1D1) x 2",
+            "(4F + 1) x 2",
+            "(SLASH 1D8) + 3",
+            "-(hit 1D20 KeepBest(1) +5 - 1)",
+            "= 1D8 + (FIRE 1D6 KeepBest(1)) * 2 - 10 / 3 up",
+            "= 1D8 + 1D6 + 4 - 1",
+            "= 2D6 + 3 x 2",
+            "$str = 3; attack = 1D20 + $str; attack x2",
+        ];
+        for request in requests.iter() {
+            let read = read_request(request).unwrap();
+            let canonical = read.to_request_string().unwrap();
+            assert_eq!(read_request(&canonical).unwrap(), read, "{}", canonical);
+        }
+    }
+
+    #[test]
+    fn expression_string_round_trip() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut checked = 0;
+        while checked < 500 {
+            let mut request = MultiTypeRequest::default();
+            // A plain sum is read as a list of dice
+            let expression = match random_operation(&mut rng, &mut request, 3) {
+                Expression::Operation(Operator::Add, ..)
+                | Expression::Operation(Operator::Subtract, ..) => continue,
+                expression => expression,
+            };
+            if request.numeric_requests.is_empty() && request.fudge_requests.is_empty() {
+                continue;
+            }
+            request.expression = Some(expression);
+            let canonical = request.to_request_string().unwrap();
+            assert_eq!(read_request(&canonical).unwrap(), request, "{}", canonical);
+            checked += 1;
+        }
+    }
+
+    #[test]
+    fn histogram_to_string() {
+        let mut histogram = Histogram::new();
+        histogram.add_occurences(9, 1);
+        histogram.add_occurences(10, 3);
+        assert_eq!(
+            histogram.to_string(),
+            format!(
+                " 9 | {:<50} 25.0%\n10 | {} 75.0%",
+                "#".repeat(16),
+                "#".repeat(50)
+            )
+        );
+    }
 }
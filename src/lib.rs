@@ -2,25 +2,41 @@ pub mod actions;
 pub mod dice;
 pub mod errors;
 pub mod io;
+pub mod probability;
+pub mod simulation;
 extern crate pest;
 #[macro_use]
 extern crate pest_derive;
+#[cfg(feature = "json")]
+#[macro_use]
+extern crate serde_derive;
 
 pub use crate::actions::Action;
 use crate::actions::*;
 use crate::dice::*;
 use crate::errors::Error;
+use crate::io::write::describe_with_audit;
 use core::fmt::Debug;
+use std::convert::TryFrom;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct TypedRollSession<T: RollBounds, V: DiceBounds> {
     pub(crate) requests: Vec<RollRequest<V>>,
     pub rolls: Vec<Rolls<T, V>>,
+    #[cfg_attr(feature = "json", serde(skip))]
     dice: DiceGenerator,
+    /// Global actions applied so far, to apply them again when a dice group is rolled twice
+    #[cfg_attr(feature = "json", serde(skip))]
+    actions: Vec<Action>,
 }
 
 pub type NumericSession = TypedRollSession<NumericRoll, NumericDice>;
 pub type FudgeSession = TypedRollSession<FudgeRoll, FudgeDice>;
+pub type SymbolSession = TypedRollSession<SymbolRoll, SymbolDice>;
+
+/// Rolls of each request before their actions, and the session after them.
+type RolledSession<T, V> = (Vec<Rolls<T, V>>, TypedRollSession<T, V>);
 
 impl<T: RollBounds, V: DiceBounds> TypedRollSession<T, V> {
     pub fn build(dice_requests: Vec<RollRequest<V>>) -> TypedRollSession<T, V>
@@ -29,7 +45,8 @@ impl<T: RollBounds, V: DiceBounds> TypedRollSession<T, V> {
         dice::DiceGenerator: dice::Roll<T, V>,
     {
         TypedRollSession::build_with_actions(dice_requests)
-            // TODO for now, without action, there's no reason for it to fail. But who can know what the future holds?
+            // Without action, only the default limits of the dice generator can make it fail
+            // TODO return the error instead
             .expect("How did this happen to us?")
     }
 
@@ -40,16 +57,118 @@ impl<T: RollBounds, V: DiceBounds> TypedRollSession<T, V> {
         Rolls<T, V>: Apply<T, V>,
         dice::DiceGenerator: dice::Roll<T, V>,
     {
-        let dice = DiceGenerator::new();
-        let rolls: Result<Vec<Rolls<T, V>>, Error> = requests
+        TypedRollSession::build_with_dice(requests, DiceGenerator::new())
+    }
+
+    /// Build the session using the given dice generator, for example a seeded one
+    /// to get reproducible rolls.
+    pub fn build_with_dice(
+        requests: Vec<RollRequest<V>>,
+        dice: DiceGenerator,
+    ) -> Result<TypedRollSession<T, V>, Error>
+    where
+        Rolls<T, V>: Apply<T, V>,
+        dice::DiceGenerator: dice::Roll<T, V>,
+    {
+        dice.reset_rolled();
+        Ok(TypedRollSession::roll_requests(requests, dice)?.1)
+    }
+
+    /// Build the session by rolling each request and applying its actions,
+    /// and return the rolls of each request before their actions too.
+    ///
+    /// The requests dealing critical damage are rolled last, with twice their dice
+    /// if another request rolled a critical success.
+    pub(crate) fn roll_requests(
+        requests: Vec<RollRequest<V>>,
+        dice: DiceGenerator,
+    ) -> Result<RolledSession<T, V>, Error>
+    where
+        Rolls<T, V>: Apply<T, V>,
+        dice::DiceGenerator: dice::Roll<T, V>,
+    {
+        let mut raw_rolls = requests
+            .iter()
+            .map(|request| {
+                if request.deals_critical_damage() {
+                    Ok(None)
+                } else {
+                    Rolls::new(request.clone(), &dice).map(Some)
+                }
+            })
+            .collect::<Result<Vec<Option<Rolls<T, V>>>, Error>>()?;
+        let mut rolls = requests
             .iter()
-            .map(|dice_request| dice_request.roll(&dice))
+            .zip(raw_rolls.iter())
+            .map(|(request, raw)| match raw {
+                Some(raw) => request.apply_actions(raw.clone(), &dice).map(Some),
+                None => Ok(None),
+            })
+            .collect::<Result<Vec<Option<Rolls<T, V>>>, Error>>()?;
+
+        let critical_success = rolls
+            .iter()
+            .flatten()
+            .any(|rolls| rolls.critical() == Some(Critical::Success));
+        let requests: Vec<RollRequest<V>> = requests
+            .into_iter()
+            .map(|request| {
+                if critical_success && request.deals_critical_damage() {
+                    request.with_critical_damage()
+                } else {
+                    request
+                }
+            })
             .collect();
-        Ok(TypedRollSession {
-            requests: requests,
-            rolls: rolls?,
-            dice,
-        })
+        for ((request, raw), rolls) in requests
+            .iter()
+            .zip(raw_rolls.iter_mut())
+            .zip(rolls.iter_mut())
+            .filter(|((_, raw), _)| raw.is_none())
+        {
+            let damage = Rolls::new(request.clone(), &dice)?;
+            *rolls = Some(request.apply_actions(damage.clone(), &dice)?);
+            *raw = Some(damage);
+        }
+
+        let raw_rolls = raw_rolls.into_iter().flatten().collect();
+        let rolls = rolls.into_iter().flatten().collect();
+        Ok((
+            raw_rolls,
+            TypedRollSession {
+                requests,
+                rolls,
+                dice,
+                actions: vec![],
+            },
+        ))
+    }
+
+    /// Critical status of the rolls: a critical success wins over a critical failure.
+    pub fn critical(&self) -> Option<Critical> {
+        Critical::combine(self.rolls.iter().filter_map(|rolls| rolls.critical()))
+    }
+
+    /// Roll each dice group again, with its actions and the global actions applied so far,
+    /// then apply the action to both rolls of the group.
+    fn roll_groups_twice(&mut self, action: &Action) -> Result<(), Error>
+    where
+        Rolls<T, V>: Apply<T, V>,
+        dice::DiceGenerator: dice::Roll<T, V>,
+    {
+        if self.actions.contains(&Action::Total) || self.rolls.len() != self.requests.len() {
+            return Err(Error::incompatible(&action.to_string(), "aggregated rolls"));
+        }
+        let mut rolls = vec![];
+        for (request, first) in self.requests.iter().zip(self.rolls.iter()) {
+            let mut other = request.roll(&self.dice)?;
+            for previous in self.actions.iter() {
+                other = other.apply(previous, &self.dice)?;
+            }
+            rolls.push(first.apply_twice(action, &other)?);
+        }
+        self.rolls = rolls;
+        Ok(())
     }
 }
 
@@ -66,51 +185,506 @@ pub trait TransformableSession: Debug + ToString + Sized {
 impl TransformableSession for NumericSession {
     fn add_transformation(&mut self, action: actions::Action) -> Result<(), Error> {
         match action {
-            Action::Total => self.rolls = vec![self.rolls.total()],
+            Action::Total => self.rolls = vec![self.rolls.total()?],
+            _ if action.rolls_group_twice() => self.roll_groups_twice(&action)?,
             _ => {
                 for rolls in self.rolls.iter_mut() {
                     *rolls = rolls.apply(&action, &self.dice)?;
                 }
             }
         }
+        self.actions.push(action);
         Ok(())
     }
 }
 
 impl TransformableSession for FudgeSession {
+    fn add_transformation(&mut self, action: actions::Action) -> Result<(), Error> {
+        if action.rolls_group_twice() {
+            self.roll_groups_twice(&action)?;
+        } else {
+            for rolls in self.rolls.iter_mut() {
+                *rolls = rolls.apply(&action, &self.dice)?;
+            }
+        }
+        self.actions.push(action);
+        Ok(())
+    }
+}
+
+impl TransformableSession for SymbolSession {
     fn add_transformation(&mut self, action: actions::Action) -> Result<(), Error> {
         for rolls in self.rolls.iter_mut() {
             *rolls = rolls.apply(&action, &self.dice)?;
         }
+        self.actions.push(action);
         Ok(())
     }
 }
 
 pub trait AggregatableSession: Debug {
-    fn aggregate(self, action: &Aggregation) -> NumericSession;
+    fn aggregate(self, action: &Aggregation) -> Result<NumericSession, Error>;
 }
 
 impl AggregatableSession for NumericSession {
-    fn aggregate(self, action: &Aggregation) -> NumericSession {
-        // TODO other kind of aggregation ?
+    fn aggregate(self, action: &Aggregation) -> Result<NumericSession, Error> {
         match action {
-            Aggregation::CountValues => self.count(),
+            Aggregation::CountValues => Ok(self.count()),
+            Aggregation::SumById => self.sum_by_id(),
+            Aggregation::CancelSymbols => {
+                Err(Error::incompatible(&action.to_string(), "numeric roll"))
+            }
         }
     }
 }
 
 impl AggregatableSession for FudgeSession {
-    fn aggregate(self, action: &Aggregation) -> NumericSession {
+    fn aggregate(self, action: &Aggregation) -> Result<NumericSession, Error> {
         match action {
-            Aggregation::CountValues => self.count(),
+            Aggregation::CountValues => Ok(self.count()),
+            Aggregation::SumById | Aggregation::CancelSymbols => {
+                Err(Error::incompatible(&action.to_string(), "fudge roll"))
+            }
+        }
+    }
+}
+
+impl AggregatableSession for SymbolSession {
+    fn aggregate(self, action: &Aggregation) -> Result<NumericSession, Error> {
+        match action {
+            Aggregation::CountValues => Ok(self.count()),
+            Aggregation::CancelSymbols => Ok(self.cancel()),
+            Aggregation::SumById => Err(Error::incompatible(&action.to_string(), "symbol roll")),
+        }
+    }
+}
+
+/// Rounding of the quotient of a division
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub enum Rounding {
+    Up,
+    Down,
+}
+
+impl Rounding {
+    /// Divide `dividend` by `divisor`, rounding the quotient toward +∞ (`Up`) or −∞ (`Down`).
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::Rounding;
+    /// assert_eq!(Rounding::Up.divide(7, 2).unwrap(), 4);
+    /// assert_eq!(Rounding::Down.divide(7, 2).unwrap(), 3);
+    /// assert_eq!(Rounding::Down.divide(-7, 2).unwrap(), -4);
+    /// assert!(Rounding::Up.divide(7, 0).is_err());
+    /// ```
+    pub fn divide(self, dividend: i64, divisor: i64) -> Result<i64, Error> {
+        if divisor == 0 {
+            return Err(Error::bad_action_parameter("Division by zero"));
         }
+        let quotient = dividend / divisor;
+        let remainder = dividend % divisor;
+        // The truncated quotient is below the exact one when the remainder and divisor have the same sign
+        let truncated_below = (remainder < 0) == (divisor < 0);
+        Ok(match (self, remainder) {
+            (_, 0) => quotient,
+            (Rounding::Up, _) if truncated_below => quotient + 1,
+            (Rounding::Down, _) if !truncated_below => quotient - 1,
+            _ => quotient,
+        })
+    }
+}
+
+/// Arithmetic operator between two expressions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide(Rounding),
+}
+
+impl Operator {
+    pub fn apply(self, left: i64, right: i64) -> Result<i64, Error> {
+        let result = match self {
+            Operator::Add => left.checked_add(right),
+            Operator::Subtract => left.checked_sub(right),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Divide(rounding) => Some(rounding.divide(left, right)?),
+        };
+        result.ok_or_else(|| Error::bad_action_parameter("Arithmetic overflow"))
+    }
+}
+
+/// Arithmetic expression over the totals of dice groups, like `(2D6 + 3) x 2` or `(4F + 2) x 2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub enum Expression {
+    /// Total of the numeric request at this index
+    Dice(usize),
+    /// Total of the fudge request at this index, each roll counting for +1, 0 or -1
+    Fudge(usize),
+    Constant(NumericRoll),
+    Operation(Operator, Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    /// Compute the expression from the rolls of its numeric and fudge requests, keeping every intermediate value.
+    ///
+    /// The fudge rolls are given already converted to numeric rolls, in the order of the fudge requests.
+    pub fn evaluate(
+        &self,
+        rolls: &[NumericRolls],
+        fudge_rolls: &[NumericRolls],
+    ) -> Result<EvaluatedExpression, Error> {
+        match self {
+            Expression::Dice(index) => Ok(EvaluatedExpression::Dice(
+                describe_with_audit(&rolls[*index]),
+                checked_sum(&rolls[*index].rolls)?,
+            )),
+            Expression::Fudge(index) => Ok(EvaluatedExpression::Dice(
+                describe_with_audit(&fudge_rolls[*index]),
+                fudge_rolls[*index].rolls.iter().sum(),
+            )),
+            Expression::Constant(value) => Ok(EvaluatedExpression::Constant(*value)),
+            Expression::Operation(operator, left, right) => {
+                let left = left.evaluate(rolls, fudge_rolls)?;
+                let right = right.evaluate(rolls, fudge_rolls)?;
+                let value = operator.apply(i64::from(left.value()), i64::from(right.value()))?;
+                let value = NumericRoll::try_from(value)
+                    .map_err(|_| Error::bad_action_parameter("Arithmetic overflow"))?;
+                Ok(EvaluatedExpression::Operation(
+                    *operator,
+                    Box::new(left),
+                    Box::new(right),
+                    value,
+                ))
+            }
+        }
+    }
+}
+
+/// Evaluation tree of an [Expression](enum.Expression.html), with the value of each node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub enum EvaluatedExpression {
+    /// Description and total of the rolls of a dice group
+    Dice(String, NumericRoll),
+    Constant(NumericRoll),
+    Operation(
+        Operator,
+        Box<EvaluatedExpression>,
+        Box<EvaluatedExpression>,
+        NumericRoll,
+    ),
+}
+
+impl EvaluatedExpression {
+    pub fn value(&self) -> NumericRoll {
+        match self {
+            EvaluatedExpression::Dice(_, value)
+            | EvaluatedExpression::Constant(value)
+            | EvaluatedExpression::Operation(_, _, _, value) => *value,
+        }
+    }
+}
+
+/// A roll request read from an input, before any dice is rolled.
+///
+/// Numeric, fudge and symbol dice requests are kept apart, since they are rolled in separate sessions.
+/// The global actions and the aggregation apply to all the sessions.
+///
+/// When the request is an arithmetic expression, the numeric requests are the dice groups
+/// of the expression, which is evaluated instead of the total.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct MultiTypeRequest {
+    pub(crate) numeric_requests: Vec<NumericRollRequest>,
+    pub(crate) fudge_requests: Vec<FudgeRollRequest>,
+    pub(crate) symbol_requests: Vec<SymbolRollRequest>,
+    pub(crate) actions: Vec<Action>,
+    pub(crate) aggregation: Option<Aggregation>,
+    pub(crate) expression: Option<Expression>,
+}
+
+impl MultiTypeRequest {
+    /// Check that the success levels of a skill check are not added up, since they are not numbers.
+    pub(crate) fn check_skill_levels(&self) -> Result<(), Error> {
+        let is_skill_check = |action: &Action| matches!(action, Action::SkillCheck(_));
+        let is_total = |action: &&Action| matches!(action, Action::Sum | Action::Total);
+        let totaled_after_check = |actions: &[Action]| {
+            actions
+                .iter()
+                .skip_while(|action| !is_skill_check(action))
+                .find(is_total)
+                .cloned()
+        };
+        let mut totaled = self
+            .numeric_requests
+            .iter()
+            .find_map(|request| totaled_after_check(&request.actions))
+            .or_else(|| totaled_after_check(&self.actions));
+        let checked_group = self
+            .numeric_requests
+            .iter()
+            .flat_map(|request| request.actions.iter())
+            .find(|action| is_skill_check(action));
+        if let Some(check) = checked_group {
+            if self.expression.is_some() {
+                return Err(Error::incompatible(
+                    &check.to_string(),
+                    "arithmetic expression",
+                ));
+            }
+            totaled = totaled.or_else(|| self.actions.iter().find(is_total).cloned());
+        }
+        match totaled {
+            Some(action) => Err(Error::incompatible(&action.to_string(), "skill levels")),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether some rolls of the request are turned into success levels by a skill check.
+    fn has_skill_levels(&self) -> bool {
+        self.numeric_requests
+            .iter()
+            .flat_map(|request| request.actions.iter())
+            .chain(self.actions.iter())
+            .any(|action| matches!(action, Action::SkillCheck(_)))
+    }
+
+    /// Roll the dice of the request, then apply the global actions and aggregation.
+    ///
+    /// # Arguments
+    /// * `default_total` If set to `true`, in the absence of a parsed aggregation, the `ToTal` action will be applied to numeric rolls,
+    ///   and the symbols of the symbol rolls will cancel each other.
+    /// * `dice` Dice generator used for all the rolls of the request, whose [Limits](dice/struct.Limits.html) apply to the whole request
+    pub fn roll(
+        &self,
+        default_total: bool,
+        dice: DiceGenerator,
+    ) -> Result<MultiTypeSession, Error> {
+        self.check_skill_levels()?;
+        let mut res = MultiTypeSession {
+            numeric_raw_rolls: vec![],
+            fudge_raw_rolls: vec![],
+            symbol_raw_rolls: vec![],
+            numeric_session: None,
+            fudge_session: None,
+            symbol_session: None,
+            symbol_results: None,
+            expression: None,
+            critical: None,
+            fudge_totaled: false,
+        };
+
+        // The global actions from Total apply to the total of both kinds of rolls
+        let (group_actions, total_actions) = match self
+            .actions
+            .iter()
+            .position(|action| *action == Action::Total)
+        {
+            Some(position) => self.actions.split_at(position),
+            None => (&self.actions[..], &self.actions[0..0]),
+        };
+        let default_total = default_total
+            && self.actions.is_empty()
+            && self.aggregation.is_none()
+            && self.expression.is_none()
+            && !self.has_skill_levels();
+
+        dice.reset_rolled();
+        let mut numeric_session = None;
+        if !self.numeric_requests.is_empty() {
+            let (raw_rolls, mut session) =
+                NumericSession::roll_requests(self.numeric_requests.clone(), dice.clone())?;
+            res.numeric_raw_rolls = raw_rolls;
+            session.add_actions(group_actions.to_vec())?;
+            res.critical = session.critical();
+            numeric_session = Some(session);
+        }
+        let mut fudge_session = None;
+        if !self.fudge_requests.is_empty() {
+            let (raw_rolls, mut session) =
+                FudgeSession::roll_requests(self.fudge_requests.clone(), dice.clone())?;
+            res.fudge_raw_rolls = raw_rolls;
+            session.add_actions(group_actions.to_vec())?;
+            fudge_session = Some(session);
+        }
+        if !self.symbol_requests.is_empty() {
+            let (raw_rolls, mut session) =
+                SymbolSession::roll_requests(self.symbol_requests.clone(), dice.clone())?;
+            res.symbol_raw_rolls = raw_rolls;
+            session.add_actions(group_actions.to_vec())?;
+            res.symbol_session = Some(session);
+        }
+        // Fudge rolls are totaled by their value, like numeric rolls, when the request asks for it
+        let mut fudge_rolls: Vec<NumericRolls> = vec![];
+        if !total_actions.is_empty() || self.expression.is_some() {
+            if let Some(session) = fudge_session.take() {
+                fudge_rolls = session.rolls.iter().map(NumericRolls::from).collect();
+                let numeric_session = numeric_session.get_or_insert_with(|| NumericSession {
+                    requests: vec![],
+                    rolls: vec![],
+                    dice: dice.clone(),
+                    actions: vec![],
+                });
+                if self.expression.is_none() {
+                    numeric_session.rolls.extend(fudge_rolls.iter().cloned());
+                }
+                // The Fate ladder is about a total of fudge rolls and modifiers,
+                // not about a count of successes or a total of numbered dice
+                res.fudge_totaled = self
+                    .numeric_requests
+                    .iter()
+                    .all(|request| matches!(request.dice, NumericDice::ConstDice(_)))
+                    && !total_actions
+                        .iter()
+                        .any(|action| matches!(action, Action::CountSuccesses(..)));
+            }
+        }
+
+        if let Some(mut session) = numeric_session {
+            session.add_actions(total_actions.to_vec())?;
+            if let Some(expression) = &self.expression {
+                let evaluated = expression.evaluate(&session.rolls, &fudge_rolls)?;
+                session.rolls = vec![Rolls {
+                    description: format!("Detailed rolls\t: {}\nRESULT \t", evaluated),
+                    dice: NumericDice::AggregationResult,
+                    id: None,
+                    rolls: vec![evaluated.value()],
+                    history: vec![],
+                }];
+                res.expression = Some(evaluated);
+            } else if let Some(aggregation) = self.aggregation {
+                session = session.aggregate(&aggregation)?;
+            } else if default_total {
+                session.add_transformation(Action::Total)?;
+                // The fudge rolls are shown apart, so the default total only adds up the numeric rolls
+                if fudge_session.is_some() {
+                    for rolls in session.rolls.iter_mut() {
+                        rolls.description =
+                            rolls
+                                .description
+                                .replacen("\nTOTAL SUM", "\nNUMERIC SUM", 1);
+                    }
+                }
+            }
+            res.numeric_session = Some(session);
+        }
+        if let Some(session) = fudge_session {
+            if let Some(aggregation) = self.aggregation {
+                let mut num_session = session.aggregate(&aggregation)?;
+                if let Some(numeric_session) = &mut res.numeric_session {
+                    numeric_session.rolls.append(&mut num_session.rolls);
+                } else {
+                    res.numeric_session = Some(num_session);
+                }
+            } else {
+                res.fudge_session = Some(session);
+            }
+        }
+        // Symbols are never totaled with numbers, they cancel each other instead.
+        // The symbol rolls are kept, since the faces can't be told from the results.
+        if let Some(session) = &res.symbol_session {
+            let aggregation = match self.aggregation {
+                None if !total_actions.is_empty() || default_total => {
+                    Some(Aggregation::CancelSymbols)
+                }
+                aggregation => aggregation,
+            };
+            if let Some(aggregation) = aggregation {
+                res.symbol_results = Some(session.clone().aggregate(&aggregation)?);
+            }
+        }
+
+        Ok(res)
     }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "json", derive(Serialize))]
 pub struct MultiTypeSession {
+    numeric_raw_rolls: Vec<NumericRolls>,
+    fudge_raw_rolls: Vec<FudgeRolls>,
+    symbol_raw_rolls: Vec<SymbolRolls>,
     numeric_session: Option<NumericSession>,
     fudge_session: Option<FudgeSession>,
+    symbol_session: Option<SymbolSession>,
+    /// Counts or cancelled symbols of the symbol rolls
+    symbol_results: Option<NumericSession>,
+    expression: Option<EvaluatedExpression>,
+    critical: Option<Critical>,
+    /// `true` if the total only adds fudge rolls and modifiers, to show it on the Fate ladder
+    #[cfg_attr(feature = "json", serde(skip))]
+    fudge_totaled: bool,
+}
+
+impl MultiTypeSession {
+    /// Rolls of each numeric dice request, before any action.
+    pub fn numeric_raw_rolls(&self) -> &[NumericRolls] {
+        &self.numeric_raw_rolls
+    }
+
+    /// Rolls of each fudge dice request, before any action.
+    pub fn fudge_raw_rolls(&self) -> &[FudgeRolls] {
+        &self.fudge_raw_rolls
+    }
+
+    /// Rolls of each symbol dice request.
+    pub fn symbol_raw_rolls(&self) -> &[SymbolRolls] {
+        &self.symbol_raw_rolls
+    }
+
+    /// Results of the symbol rolls, when they were aggregated, or cancelled instead of being totaled
+    /// (cf. [CancelSymbols](actions/trait.CancelSymbols.html)).
+    pub fn symbol_results(&self) -> Option<&NumericSession> {
+        self.symbol_results.as_ref()
+    }
+
+    /// Critical status of the numeric rolls, before they are totaled, if a group checked it with the [Critical](actions/enum.Action.html#variant.Critical) action.
+    pub fn critical(&self) -> Option<Critical> {
+        self.critical
+    }
+
+    /// Evaluation tree of the request, if it was an arithmetic expression.
+    pub fn expression(&self) -> Option<&EvaluatedExpression> {
+        self.expression.as_ref()
+    }
+
+    /// Single result of the request, when all its rolls were totaled (fudge rolls counting for +1, 0 or -1)
+    /// or evaluated as an arithmetic expression, and possibly compared to a target afterwards.
+    /// Without the `Total` action, the default total of numeric and fudge rolls only adds up the numeric rolls,
+    /// labelled `NUMERIC SUM`, and the fudge rolls are kept apart: there is no single result.
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::io::read::read_request;
+    /// # use letsroll::dice::DiceGenerator;
+    /// let session = read_request("4F +2 Total").unwrap().roll(true, DiceGenerator::from_seed(42)).unwrap();
+    /// let fudge_total: i64 = session.fudge_raw_rolls()[0].rolls.iter().map(|roll| roll.value()).sum();
+    /// assert_eq!(session.total(), Some(2 + fudge_total as i32));
+    ///
+    /// let session = read_request("4F +2").unwrap().roll(true, DiceGenerator::from_seed(42)).unwrap();
+    /// assert_eq!(session.total(), None);
+    /// ```
+    pub fn total(&self) -> Option<NumericRoll> {
+        match (
+            &self.numeric_session,
+            &self.fudge_session,
+            &self.symbol_session,
+        ) {
+            (Some(session), None, None) => match session.rolls.as_slice() {
+                [rolls] => match rolls.rolls.as_slice() {
+                    [total] => Some(*total),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
 }
 
 impl TransformableSession for MultiTypeSession {
@@ -121,6 +695,9 @@ impl TransformableSession for MultiTypeSession {
         if let Some(ref mut session) = &mut self.fudge_session {
             session.add_transformation(action.clone())?;
         }
+        if let Some(ref mut session) = &mut self.symbol_session {
+            session.add_transformation(action.clone())?;
+        }
         Ok(())
     }
 }
@@ -132,4 +709,186 @@ mod tests {
     // use crate::RollRequest;
 
     // TODO
+
+    #[test]
+    fn critical_damage() {
+        use crate::actions::Action;
+        use crate::dice::{Critical, DiceGenerator, NumericDice, NumericRollRequest};
+        use crate::NumericSession;
+
+        // The first value of the repeating dice is the natural roll
+        let requests = |natural: Vec<i32>| {
+            vec![
+                NumericRollRequest::new(1, NumericDice::RepeatingDice(natural))
+                    .add_action(Action::Critical(None)),
+                NumericRollRequest::new(2, NumericDice::NumberedDice(8))
+                    .add_action(Action::CriticalDamage),
+                NumericRollRequest::new(1, NumericDice::ConstDice(3)),
+            ]
+        };
+        let session =
+            NumericSession::build_with_dice(requests(vec![20, 1]), DiceGenerator::from_seed(1))
+                .unwrap();
+        assert_eq!(session.critical(), Some(Critical::Success));
+        // Only the dice are doubled, not the modifiers
+        assert_eq!(session.rolls[1].rolls.len(), 4);
+        assert_eq!(session.rolls[1].description, "4D8 CritDamage");
+        assert_eq!(session.rolls[2].rolls, vec![3]);
+
+        let session =
+            NumericSession::build_with_dice(requests(vec![1, 20]), DiceGenerator::from_seed(1))
+                .unwrap();
+        assert_eq!(session.critical(), Some(Critical::Failure));
+        assert_eq!(session.rolls[1].rolls.len(), 2);
+    }
+
+    #[test]
+    fn fudge_total() {
+        use crate::dice::DiceGenerator;
+        use crate::io::read::read_request;
+        use crate::io::write::fate_ladder;
+
+        let session = read_request("4F +3 Total")
+            .unwrap()
+            .roll(true, DiceGenerator::from_seed(2))
+            .unwrap();
+        let total = 3 + session.fudge_raw_rolls()[0]
+            .rolls
+            .iter()
+            .map(|roll| roll.value())
+            .sum::<i64>();
+        assert!(session.fudge_session.is_none());
+        assert_eq!(
+            session.numeric_session.as_ref().unwrap().rolls[0].rolls,
+            vec![total as i32]
+        );
+        assert!(session
+            .to_string()
+            .ends_with(&format!("FATE LADDER \t: {}", fate_ladder(total))));
+
+        // Compared to a target, the total is no longer on the Fate ladder
+        let session = read_request("4F +3 Total Successes(>=4)")
+            .unwrap()
+            .roll(true, DiceGenerator::from_seed(2))
+            .unwrap();
+        assert_eq!(session.total(), Some(if total >= 4 { 1 } else { 0 }));
+        assert!(!session.to_string().contains("FATE LADDER"));
+        // Neither is a total of numbered dice
+        let session = read_request("10D20 4F Total")
+            .unwrap()
+            .roll(true, DiceGenerator::from_seed(2))
+            .unwrap();
+        assert!(session.total().is_some());
+        assert!(!session.to_string().contains("FATE LADDER"));
+        let session = read_request("(4F + 1) x 2")
+            .unwrap()
+            .roll(true, DiceGenerator::from_seed(2))
+            .unwrap();
+        assert!(session.to_string().contains("FATE LADDER"));
+
+        // Without the Total action, the fudge rolls are kept apart from the default total
+        let session = read_request("4F +3")
+            .unwrap()
+            .roll(true, DiceGenerator::from_seed(2))
+            .unwrap();
+        assert_eq!(session.total(), None);
+        assert_eq!(
+            session.numeric_session.as_ref().unwrap().rolls[0].rolls,
+            vec![3]
+        );
+        assert_eq!(
+            session.fudge_session.as_ref().unwrap().rolls[0].rolls.len(),
+            4
+        );
+        assert!(session.to_string().contains("\nNUMERIC SUM \t: 3\n"));
+        assert!(!session.to_string().contains("TOTAL SUM"));
+        let session = read_request("10D20 4F")
+            .unwrap()
+            .roll(true, DiceGenerator::from_seed(2))
+            .unwrap();
+        assert_eq!(session.total(), None);
+        assert!(session.fudge_session.is_some());
+        assert!(!session.to_string().contains("FATE LADDER"));
+        assert!(read_request("4F Count")
+            .unwrap()
+            .roll(true, DiceGenerator::from_seed(2))
+            .unwrap()
+            .fudge_session
+            .is_none());
+    }
+
+    #[test]
+    fn symbol_cancel() {
+        use crate::dice::{DiceGenerator, Symbol};
+        use crate::io::read::read_request;
+
+        let session = read_request("3Ability 2Difficulty")
+            .unwrap()
+            .roll(true, DiceGenerator::from_seed(4))
+            .unwrap();
+        assert_eq!(session.total(), None);
+        assert_eq!(session.symbol_raw_rolls().len(), 2);
+        let count = |symbol: Symbol| {
+            session
+                .symbol_raw_rolls()
+                .iter()
+                .flat_map(|rolls| rolls.rolls.iter())
+                .flat_map(|roll| roll.symbols())
+                .filter(|rolled| *rolled == symbol)
+                .count() as i32
+        };
+        let net_successes = count(Symbol::Success) - count(Symbol::Failure);
+        let results = &session.symbol_results().unwrap().rolls;
+        let success = results
+            .iter()
+            .find(|rolls| rolls.description == "SUCCESS" || rolls.description == "FAILURE");
+        match success {
+            Some(rolls) if rolls.description == "SUCCESS" => {
+                assert_eq!(rolls.rolls, vec![net_successes])
+            }
+            Some(rolls) => assert_eq!(rolls.rolls, vec![-net_successes]),
+            None => assert_eq!(net_successes, 0),
+        }
+
+        // Counted, the symbols don't cancel each other
+        let session = read_request("3Ability 2Difficulty Count")
+            .unwrap()
+            .roll(true, DiceGenerator::from_seed(4))
+            .unwrap();
+        let results = &session.symbol_results().unwrap().rolls;
+        assert!(results
+            .iter()
+            .all(|rolls| rolls.description.starts_with("COUNT(")));
+        assert!(read_request("3Ability SumById")
+            .unwrap()
+            .roll(true, DiceGenerator::from_seed(4))
+            .is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn session_to_json() {
+        use crate::dice::DiceGenerator;
+        use crate::io::read::read_request;
+
+        let request = read_request("(FIRE 3D1 Reroll(1)) 2F").unwrap();
+        let session = request.roll(true, DiceGenerator::from_seed(1)).unwrap();
+        assert_eq!(session.numeric_raw_rolls()[0].rolls, vec![1, 1, 1]);
+        assert_eq!(session.fudge_raw_rolls()[0].rolls.len(), 2);
+
+        let json = serde_json::to_value(&session).unwrap();
+        assert_eq!(json["numeric_raw_rolls"][0]["id"], "FIRE");
+        assert_eq!(
+            json["numeric_session"]["requests"][0]["actions"][0]["RerollNumeric"][0]["Values"][0],
+            1
+        );
+        // Without the Total action, the fudge rolls are kept apart
+        assert_eq!(json["numeric_session"]["rolls"][0]["rolls"][0], 3);
+        assert!(!json["fudge_session"].is_null());
+        assert!(json.get("dice").is_none());
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["fudge_requests"][0]["dice"],
+            "FudgeDice"
+        );
+    }
 }
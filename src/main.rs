@@ -3,30 +3,106 @@ extern crate serde_derive;
 extern crate docopt;
 use docopt::Docopt;
 
+use letsroll::dice::{DiceGenerator, Limits};
 use letsroll::errors::Error;
+use letsroll::io::read::{read_document, read_request, read_roll_file};
+use letsroll::io::repl::Repl;
+use letsroll::io::write::ToRequestString;
+use letsroll::MultiTypeRequest;
+#[cfg(feature = "json")]
+use letsroll::{simulation::Simulation, MultiTypeSession};
 
-use letsroll;
-use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::Path;
 // Write the Docopt usage string.
-const USAGE: &'static str = "
-Usage: letsroll <dice> [-s <savepath>]
-       letsroll -f <filename> [-s <savepath>]
+const USAGE: &str = "
+Usage: letsroll <dice> [-s <savepath>] [--seed <seed>] [--simulate <runs>] [--format <format>] [--max-dice <n>] [--max-total-dice <n>] [--max-explosions <n>] [--max-rerolls <n>]
+       letsroll -f <filename> [<label>] [-s <savepath>] [--seed <seed>] [--simulate <runs>] [--format <format>] [--max-dice <n>] [--max-total-dice <n>] [--max-explosions <n>] [--max-rerolls <n>]
+       letsroll (-i | --interactive) [--seed <seed>] [--max-dice <n>] [--max-total-dice <n>] [--max-explosions <n>] [--max-rerolls <n>]
        letsroll (-h | --help)
 
 Options:
-    -h --help    Show this screen.
-    -f, --file   Read the dice request from a file.
-    -s, --save   Saves the rolls request to a file for future use. Tip: use .roll file extension!
+    -h --help       Show this screen.
+    -f, --file      Read the dice requests from a .roll file, and roll them all or only the ones with the given label.
+    -s <savepath>, --save <savepath>    Saves the rolls request to a file for future use. Tip: use .roll file extension!
+    -i, --interactive   Roll the requests read line by line, until 'exit' or the end of the input.
+                        '!!' repeats the last request, 'history' lists the past rolls and
+                        ':def <name> = <request>' defines a macro usable in the next requests.
+    --seed <seed>   Seed the dice to get reproducible rolls.
+    --simulate <runs>   Roll the request many times and print statistics on the results.
+    --format <format>   Output format: text or json [default: text].
+    --max-dice <n>          Maximum number of dice rolled at once [default: 100].
+    --max-total-dice <n>    Maximum number of dice rolled for a request, rerolls and explosions included [default: 1000].
+    --max-explosions <n>    Maximum number of successive explosions of a die [default: 20].
+    --max-rerolls <n>       Maximum number of dice rerolled by an action [default: 100].
 ";
 
 #[derive(Debug, Deserialize)]
 struct Args {
     arg_dice: String,
     arg_filename: Option<String>,
-    arg_savepath: Option<String>,
+    arg_label: Option<String>,
+    flag_save: Option<String>,
+    flag_seed: Option<u64>,
+    flag_simulate: Option<u32>,
+    flag_format: Format,
+    flag_interactive: bool,
+    flag_max_dice: usize,
+    flag_max_total_dice: usize,
+    flag_max_explosions: usize,
+    flag_max_rerolls: usize,
+}
+
+impl Args {
+    fn dice(&self) -> DiceGenerator {
+        let dice = match self.flag_seed {
+            Some(seed) => DiceGenerator::from_seed(seed),
+            None => DiceGenerator::new(),
+        };
+        dice.with_limits(Limits {
+            max_dice: self.flag_max_dice,
+            max_total_dice: self.flag_max_total_dice,
+            max_explosion_depth: self.flag_max_explosions,
+            max_rerolls: self.flag_max_rerolls,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+enum Format {
+    Text,
+    Json,
+}
+
+/// A request to roll, as it was written and as it was read
+struct Request {
+    label: Option<String>,
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    text: String,
+    parsed: MultiTypeRequest,
+}
+
+/// Everything about a roll, for the JSON output
+#[cfg(feature = "json")]
+#[derive(Serialize)]
+struct JsonRolls<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+    request: &'a str,
+    parsed_request: &'a MultiTypeRequest,
+    session: &'a MultiTypeSession,
+}
+
+/// Everything about a simulation, for the JSON output
+#[cfg(feature = "json")]
+#[derive(Serialize)]
+struct JsonSimulation<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+    request: &'a str,
+    parsed_request: &'a MultiTypeRequest,
+    simulation: &'a Simulation,
 }
 
 fn main() {
@@ -40,36 +116,156 @@ fn main() {
 }
 
 fn run(args: Args) -> Result<(), Error> {
-    let request_to_parse = match &args.arg_filename {
-        Some(filename) => match fs::read_to_string(filename) {
-            Err(msg) => return Err(Error::from(msg)),
-            Ok(file_contents) => file_contents,
-        },
-        None => args.arg_dice,
+    if args.flag_interactive {
+        return run_interactive(args.dice());
+    }
+    let roll_file = match &args.arg_filename {
+        Some(filename) => Some(read_roll_file(filename)?.select(args.arg_label.as_deref())?),
+        None => None,
+    };
+    let requests = match &roll_file {
+        Some(roll_file) => roll_file
+            .requests()
+            .iter()
+            .map(|request| {
+                Ok(Request {
+                    label: request.label.clone(),
+                    text: request.request.clone(),
+                    parsed: roll_file.read_request(request)?,
+                })
+            })
+            .collect::<Result<Vec<Request>, Error>>()?,
+        None => vec![Request {
+            label: None,
+            text: args.arg_dice.clone(),
+            parsed: read_request(&args.arg_dice)?,
+        }],
     };
 
-    let roll_sessions = letsroll::io::read::parse_request(&request_to_parse, true);
-    match roll_sessions {
-        Err(msg) => return Err(msg),
-        Ok(ref req) => {
-            println!("Rolling...\n{}", req.to_string());
-            match &args.arg_savepath {
-                Some(save_path) => match write_to_file(&request_to_parse, &save_path) {
-                    Ok(_) => {
-                        println!("Wrote rolls request to file {}", save_path);
-                        Ok(())
-                    }
-                    Err(msg) => return Err(Error::from(msg)),
-                },
-                _ => Ok(()),
+    let dice = args.dice();
+    match &args.flag_format {
+        Format::Json => print_json(&requests, args.flag_simulate, dice)?,
+        Format::Text => {
+            for request in requests.iter() {
+                let label = match &request.label {
+                    Some(label) => format!(" {}", label),
+                    None => String::new(),
+                };
+                match args.flag_simulate {
+                    Some(runs) => println!(
+                        "Simulating{}...\n{}",
+                        label,
+                        request.parsed.simulate(runs, true, dice.clone())?
+                    ),
+                    None => println!(
+                        "Rolling{}...\n{}",
+                        label,
+                        request.parsed.roll(true, dice.clone())?
+                    ),
+                }
+            }
+        }
+    }
+    match &args.flag_save {
+        Some(save_path) => {
+            let content = match &roll_file {
+                Some(roll_file) => roll_file.to_string(),
+                None => request_to_save(&args.arg_dice)?,
+            };
+            match write_to_file(&content, save_path) {
+                Ok(_) => {
+                    println!("Wrote rolls request to file {}", save_path);
+                    Ok(())
+                }
+                Err(msg) => Err(Error::from(msg)),
             }
         }
+        _ => Ok(()),
+    }
+}
+
+/// Canonical form of the request, or the request as it was written if it uses definitions,
+/// so that they can be reused.
+fn request_to_save(input: &str) -> Result<String, Error> {
+    let (definitions, request) = read_document(input)?;
+    if definitions.is_empty() {
+        read_request(&request)?.to_request_string()
+    } else {
+        Ok(format!("{}\n{}", definitions, request))
     }
 }
 
-fn write_to_file(content: &String, filepath: &str) -> std::io::Result<()> {
+fn run_interactive(dice: DiceGenerator) -> Result<(), Error> {
+    let mut repl = Repl::new(dice);
+    let stdin = std::io::stdin();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            println!();
+            return Ok(());
+        }
+        match line.trim() {
+            "exit" | "quit" => return Ok(()),
+            line => match repl.eval(line) {
+                Ok(output) if output.is_empty() => (),
+                Ok(output) => println!("{}", output),
+                Err(error) => eprintln!("FAILURE : {}", error),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+fn print_json(
+    requests: &[Request],
+    simulate: Option<u32>,
+    dice: DiceGenerator,
+) -> Result<(), Error> {
+    let values = requests
+        .iter()
+        .map(|request| {
+            let label = request.label.as_deref();
+            let json = match simulate {
+                Some(runs) => serde_json::to_value(&JsonSimulation {
+                    label,
+                    request: &request.text,
+                    parsed_request: &request.parsed,
+                    simulation: &request.parsed.simulate(runs, true, dice.clone())?,
+                }),
+                None => serde_json::to_value(&JsonRolls {
+                    label,
+                    request: &request.text,
+                    parsed_request: &request.parsed,
+                    session: &request.parsed.roll(true, dice.clone())?,
+                }),
+            };
+            Ok(json.expect("Rolls can always be written as JSON"))
+        })
+        .collect::<Result<Vec<serde_json::Value>, Error>>()?;
+    // A single request is not wrapped in a list
+    let json = match values.as_slice() {
+        [value] => serde_json::to_string_pretty(value),
+        _ => serde_json::to_string_pretty(&values),
+    };
+    println!("{}", json.expect("Rolls can always be written as JSON"));
+    Ok(())
+}
+
+#[cfg(not(feature = "json"))]
+fn print_json(
+    _requests: &[Request],
+    _simulate: Option<u32>,
+    _dice: DiceGenerator,
+) -> Result<(), Error> {
+    eprintln!("FAILURE : letsroll was built without the json feature");
+    Ok(())
+}
+
+fn write_to_file(content: &str, filepath: &str) -> std::io::Result<()> {
     let path = Path::new(filepath);
 
-    let mut file = File::create(&path)?;
+    let mut file = File::create(path)?;
     file.write_all(content.to_string().as_bytes())
 }
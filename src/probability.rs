@@ -0,0 +1,911 @@
+//! `probability` computes the exact probability distribution of the result of a roll request,
+//! without rolling any dice.
+//!
+//! The result of a request is the sum of all its final rolls (fudge rolls count as +1, 0 or -1).
+//! Only the actions that can be computed analytically are supported: the others
+//! (like [FlipFlop](../actions/trait.FlipFlop.html) or [Concat](../actions/trait.Concat.html))
+//! return an error of kind `NotComputable`.
+//!
+//! The rolls that would fail because they exceed the [Limits](../dice/struct.Limits.html),
+//! like too many successive explosions, are left out of the distribution: the probabilities
+//! of a distribution then add up to slightly less than 1.
+
+use crate::actions::{Action, Aggregation, RerollMode, RollCondition, SkillLevel};
+use crate::dice::*;
+use crate::errors::Error;
+use crate::{Expression, MultiTypeRequest, Operator};
+use std::collections::{BTreeMap, HashMap};
+
+pub type Probability = f64;
+
+/// Maximum number of distinct sets of rolls tracked for one dice group.
+const MAX_POOLS: usize = 500_000;
+
+/// Maximum number of pairs of sets of rolls combined at once.
+const MAX_COMBINATIONS: usize = 50_000_000;
+
+/// Probability of each possible value of a result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution {
+    probabilities: BTreeMap<i64, Probability>,
+}
+
+impl Distribution {
+    /// Distribution of a result that is always the same value.
+    pub fn constant(value: i64) -> Distribution {
+        let mut probabilities = BTreeMap::new();
+        probabilities.insert(value, 1.0);
+        Distribution { probabilities }
+    }
+
+    /// Distribution of a result that is equally likely to be each of the values.
+    pub fn uniform<I: IntoIterator<Item = i64>>(values: I) -> Distribution {
+        let values: Vec<i64> = values.into_iter().collect();
+        let mut probabilities = BTreeMap::new();
+        for value in values.iter() {
+            *probabilities.entry(*value).or_insert(0.0) += 1.0 / values.len() as Probability;
+        }
+        Distribution { probabilities }
+    }
+
+    /// Probability of each possible value, sorted by value.
+    pub fn probabilities(&self) -> &BTreeMap<i64, Probability> {
+        &self.probabilities
+    }
+
+    /// Probability that the result is equal to the value.
+    pub fn probability(&self, value: i64) -> Probability {
+        *self.probabilities.get(&value).unwrap_or(&0.0)
+    }
+
+    /// Probability that the result is greater or equal to the value.
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::io::read::read_request;
+    /// let distribution = read_request("2D6").unwrap().distribution().unwrap();
+    /// assert!((distribution.probability_at_least(11) - 3.0 / 36.0).abs() < 1e-9);
+    /// ```
+    pub fn probability_at_least(&self, value: i64) -> Probability {
+        self.probabilities.range(value..).map(|(_, p)| p).sum()
+    }
+
+    pub fn min(&self) -> i64 {
+        *self.probabilities.keys().next().unwrap_or(&0)
+    }
+
+    pub fn max(&self) -> i64 {
+        *self.probabilities.keys().next_back().unwrap_or(&0)
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.probabilities
+            .iter()
+            .map(|(value, p)| *value as f64 * p)
+            .sum()
+    }
+
+    pub fn variance(&self) -> f64 {
+        let mean = self.mean();
+        self.probabilities
+            .iter()
+            .map(|(value, p)| (*value as f64 - mean).powi(2) * p)
+            .sum()
+    }
+
+    pub fn standard_deviation(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Distribution of the sum of two independent results.
+    fn add(&self, other: &Distribution) -> Distribution {
+        let mut probabilities = BTreeMap::new();
+        for (value, p) in self.probabilities.iter() {
+            for (other_value, q) in other.probabilities.iter() {
+                *probabilities.entry(value + other_value).or_insert(0.0) += p * q;
+            }
+        }
+        Distribution { probabilities }
+    }
+
+    /// Distribution of an arithmetic operation between two independent results.
+    fn combine(&self, other: &Distribution, operator: Operator) -> Result<Distribution, Error> {
+        let mut probabilities = BTreeMap::new();
+        for (value, p) in self.probabilities.iter() {
+            for (other_value, q) in other.probabilities.iter() {
+                *probabilities
+                    .entry(operator.apply(*value, *other_value)?)
+                    .or_insert(0.0) += p * q;
+            }
+        }
+        Ok(Distribution { probabilities })
+    }
+
+    /// Probability that a roll of this distribution is one of the values.
+    fn probability_of_any(&self, values: &[i64]) -> Probability {
+        values.iter().map(|value| self.probability(*value)).sum()
+    }
+}
+
+impl MultiTypeRequest {
+    /// Compute the exact distribution of the sum of all the final rolls of the request,
+    /// with the default [Limits](../dice/struct.Limits.html).
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::io::read::read_request;
+    /// let distribution = read_request("4D6 KeepBest(3)").unwrap().distribution().unwrap();
+    /// assert_eq!(distribution.min(), 3);
+    /// assert_eq!(distribution.max(), 18);
+    /// assert!((distribution.mean() - 12.2446).abs() < 1e-4);
+    /// ```
+    pub fn distribution(&self) -> Result<Distribution, Error> {
+        self.distribution_with_limits(&Limits::default())
+    }
+
+    /// Compute the exact distribution of the sum of all the final rolls of the request,
+    /// leaving out the rolls that would exceed the limits.
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::dice::Limits;
+    /// # use letsroll::io::read::read_request;
+    /// let limits = Limits {
+    ///     max_explosion_depth: 1,
+    ///     ..Limits::default()
+    /// };
+    /// let request = read_request("(1D6 Explode(6))").unwrap();
+    /// let distribution = request.distribution_with_limits(&limits).unwrap();
+    /// // Two sixes in a row would explode one time too many
+    /// assert_eq!(distribution.max(), 11);
+    /// ```
+    pub fn distribution_with_limits(&self, limits: &Limits) -> Result<Distribution, Error> {
+        self.check_skill_levels()?;
+        if let Some(Aggregation::CountValues) = self.aggregation {
+            return Err(Error::not_computable(&Aggregation::CountValues.to_string()));
+        }
+        // The symbols have no sum
+        if let Some(request) = self.symbol_requests.first() {
+            return Err(Error::not_computable(&request.to_string()));
+        }
+        // Global actions apply to each dice group until they are totaled
+        let total_position = self
+            .actions
+            .iter()
+            .position(|action| *action == Action::Total);
+        let (group_actions, total_actions) = match total_position {
+            Some(position) => (&self.actions[..position], &self.actions[position + 1..]),
+            None => (&self.actions[..], &self.actions[0..0]),
+        };
+
+        let mut numeric_groups: Vec<Pools> = vec![];
+        for request in self.numeric_requests.iter() {
+            let dice = numeric_dice_distribution(&request.dice)?;
+            let actions: Vec<&Action> = request.actions.iter().chain(group_actions).collect();
+            numeric_groups.push(group_pools(
+                request.number,
+                &dice,
+                &actions,
+                limits,
+                &apply_numeric,
+            )?);
+        }
+        let mut fudge_groups: Vec<Pools> = vec![];
+        for request in self.fudge_requests.iter() {
+            let dice = fudge_dice_distribution(&request.dice);
+            let actions: Vec<&Action> = request.actions.iter().chain(group_actions).collect();
+            fudge_groups.push(group_pools(
+                request.number,
+                &dice,
+                &actions,
+                limits,
+                &apply_fudge,
+            )?);
+        }
+        if let Some(expression) = &self.expression {
+            return expression_distribution(expression, &numeric_groups, &fudge_groups);
+        }
+
+        // Fudge rolls are totaled by their value, like numeric rolls
+        if total_position.is_some() {
+            let total = numeric_groups
+                .iter()
+                .chain(fudge_groups.iter())
+                .fold(Distribution::constant(0), |total, pools| {
+                    total.add(&pools_sum(pools))
+                });
+            let mut pools: Pools = total
+                .probabilities
+                .iter()
+                .map(|(value, p)| (vec![*value], *p))
+                .collect();
+            // The total is not a dice, so it can't be rerolled
+            for action in total_actions.iter() {
+                pools = apply_numeric(pools, action, None)?;
+            }
+            numeric_groups = vec![pools];
+            fudge_groups = vec![];
+        }
+
+        Ok(numeric_groups
+            .iter()
+            .chain(fudge_groups.iter())
+            .fold(Distribution::constant(0), |total, pools| {
+                total.add(&pools_sum(pools))
+            }))
+    }
+}
+
+/// Distribution of an expression, from the sets of rolls of its numeric and fudge dice groups.
+fn expression_distribution(
+    expression: &Expression,
+    groups: &[Pools],
+    fudge_groups: &[Pools],
+) -> Result<Distribution, Error> {
+    match expression {
+        Expression::Dice(index) => Ok(pools_sum(&groups[*index])),
+        Expression::Fudge(index) => Ok(pools_sum(&fudge_groups[*index])),
+        Expression::Constant(value) => Ok(Distribution::constant(i64::from(*value))),
+        Expression::Operation(operator, left, right) => {
+            expression_distribution(left, groups, fudge_groups)?.combine(
+                &expression_distribution(right, groups, fudge_groups)?,
+                *operator,
+            )
+        }
+    }
+}
+
+/// Probability of each sorted set of rolls of a dice group.
+type Pools = HashMap<Vec<i64>, Probability>;
+
+/// The dice group an action applies to.
+struct Group<'a> {
+    dice: &'a Distribution,
+    /// Largest number of successive explosions of a die
+    max_explosion_depth: usize,
+    /// Whether only the sum of the rolls matters after the action
+    sum_only: bool,
+}
+
+type ApplyToPools = dyn Fn(Pools, &Action, Option<&Group>) -> Result<Pools, Error>;
+
+fn numeric_dice_distribution(dice: &NumericDice) -> Result<Distribution, Error> {
+    match dice {
+        NumericDice::NumberedDice(sides) if *sides > 0 => {
+            Ok(Distribution::uniform((1..=*sides).map(i64::from)))
+        }
+        NumericDice::CustomDice(faces) if !faces.is_empty() => {
+            Ok(Distribution::uniform(faces.iter().cloned().map(i64::from)))
+        }
+        NumericDice::PercentileDice => Ok(Distribution::uniform(1..=100)),
+        NumericDice::ConstDice(const_value) => Ok(Distribution::constant(i64::from(*const_value))),
+        _ => Err(Error::not_computable(&dice.to_string())),
+    }
+}
+
+fn fudge_dice_distribution(dice: &FudgeDice) -> Distribution {
+    match dice {
+        FudgeDice::FudgeDice => Distribution::uniform(vec![1, 0, -1]),
+        FudgeDice::ConstDice(const_value) => Distribution::constant(const_value.value()),
+        FudgeDice::RepeatingDice(repeat_values) => {
+            Distribution::uniform(repeat_values.iter().map(|roll| roll.value()))
+        }
+    }
+}
+
+/// Compute the sets of rolls of a dice group after its actions.
+fn group_pools(
+    number: DiceNumber,
+    dice: &Distribution,
+    actions: &[&Action],
+    limits: &Limits,
+    apply: &ApplyToPools,
+) -> Result<Pools, Error> {
+    let mut pools = if only_sum_matters(actions) {
+        // Only the sum matters, no need to keep track of each roll
+        let sum = (0..number).fold(Distribution::constant(0), |sum, _| sum.add(dice));
+        sum.probabilities
+            .iter()
+            .map(|(value, p)| (vec![*value], *p))
+            .collect()
+    } else {
+        roll_pools(number as usize, dice)?
+    };
+    for (index, action) in actions.iter().enumerate() {
+        let group = Group {
+            dice,
+            max_explosion_depth: limits.max_explosion_depth,
+            sum_only: only_sum_matters(&actions[index + 1..]),
+        };
+        pools = apply(pools, action, Some(&group))?;
+    }
+    Ok(pools)
+}
+
+/// Whether the actions only need the sum of the rolls they apply to.
+fn only_sum_matters(actions: &[&Action]) -> bool {
+    actions
+        .iter()
+        .all(|action| matches!(action, Action::Sum | Action::MultiplyBy(_)))
+}
+
+/// Compute all the possible sets of rolls of `number` dice.
+fn roll_pools(number: usize, dice: &Distribution) -> Result<Pools, Error> {
+    let mut pools: Pools = HashMap::new();
+    pools.insert(vec![], 1.0);
+    for _ in 0..number {
+        let mut new_pools: Pools = HashMap::new();
+        for (pool, p) in pools.iter() {
+            for (value, q) in dice.probabilities.iter() {
+                *new_pools
+                    .entry(insert_sorted(pool, &[*value]))
+                    .or_insert(0.0) += p * q;
+            }
+            check_pools_size(&new_pools)?;
+        }
+        pools = new_pools;
+    }
+    Ok(pools)
+}
+
+fn insert_sorted(pool: &[i64], values: &[i64]) -> Vec<i64> {
+    let mut new_pool = pool.to_vec();
+    new_pool.extend_from_slice(values);
+    new_pool.sort();
+    new_pool
+}
+
+fn check_pools_size(pools: &Pools) -> Result<(), Error> {
+    if pools.len() > MAX_POOLS {
+        return Err(Error::not_computable(&String::from(
+            "too many dice combinations",
+        )));
+    }
+    Ok(())
+}
+
+fn pools_sum(pools: &Pools) -> Distribution {
+    let mut probabilities = BTreeMap::new();
+    for (pool, p) in pools.iter() {
+        *probabilities.entry(pool.iter().sum()).or_insert(0.0) += p;
+    }
+    Distribution { probabilities }
+}
+
+fn map_pools<F: Fn(&[i64]) -> Vec<i64>>(pools: Pools, f: F) -> Pools {
+    let mut new_pools: Pools = HashMap::new();
+    for (pool, p) in pools.into_iter() {
+        let mut new_pool = f(&pool);
+        new_pool.sort();
+        *new_pools.entry(new_pool).or_insert(0.0) += p;
+    }
+    new_pools
+}
+
+fn apply_numeric(pools: Pools, action: &Action, group: Option<&Group>) -> Result<Pools, Error> {
+    match action {
+        Action::Sum => Ok(map_pools(pools, |pool| vec![pool.iter().sum()])),
+        Action::MultiplyBy(factor) => Ok(map_pools(pools, |pool| {
+            pool.iter().map(|roll| roll * i64::from(*factor)).collect()
+        })),
+        Action::Negate => Ok(map_pools(pools, |pool| {
+            pool.iter().map(|roll| -roll).collect()
+        })),
+        Action::KeepBest(keep) => keep_pools(pools, *keep, |pool, keep| {
+            pool[pool.len() - keep..].to_vec()
+        }),
+        Action::KeepWorst(keep) => keep_pools(pools, *keep, |pool, keep| pool[..keep].to_vec()),
+        Action::CountSuccesses(comparison, target, botch) => Ok(map_pools(pools, |pool| {
+            let successes = pool
+                .iter()
+                .filter(|roll| comparison.compare(**roll as NumericRoll, *target))
+                .count();
+            let botches = pool
+                .iter()
+                .filter(|roll| botch.iter().any(|value| i64::from(*value) == **roll))
+                .count();
+            vec![successes as i64 - botches as i64]
+        })),
+        Action::RerollNumeric(condition, mode) => {
+            let dice = dice_to_reroll(action, group)?.dice;
+            reroll_pools(pools, dice, &matching_faces(dice, condition), *mode)
+        }
+        Action::Explode(condition) => {
+            let group = dice_to_reroll(action, group)?;
+            explode_pools(pools, group, &matching_faces(group.dice, condition))
+        }
+        Action::SkillCheck(skill) => Ok(map_pools(pools, |pool| {
+            pool.iter()
+                .map(|roll| SkillLevel::of(*roll as NumericRoll, *skill) as i64)
+                .collect()
+        })),
+        // Flagging the natural rolls doesn't change them
+        Action::Critical(_) => Ok(pools),
+        Action::Advantage | Action::Disadvantage => {
+            // The total is not a dice group, so it can't be rolled again
+            dice_to_reroll(action, group)?;
+            Ok(roll_twice_pools(&pools, *action == Action::Advantage))
+        }
+        Action::RerollFudge(_, _) | Action::ExplodeFudge(_) | Action::Total => {
+            Err(Error::incompatible(&action.to_string(), "numeric roll"))
+        }
+        Action::Concat
+        | Action::FlipFlop
+        | Action::RerollBest(_)
+        | Action::RerollWorst(_)
+        | Action::Compound(_)
+        | Action::Penetrate(_)
+        | Action::CriticalDamage
+        | Action::Bonus(_)
+        | Action::Penalty(_) => Err(Error::not_computable(&action.to_string())),
+    }
+}
+
+fn apply_fudge(pools: Pools, action: &Action, group: Option<&Group>) -> Result<Pools, Error> {
+    match action {
+        Action::RerollFudge(values, mode) => reroll_pools(
+            pools,
+            dice_to_reroll(action, group)?.dice,
+            &values
+                .iter()
+                .map(|value| value.value())
+                .collect::<Vec<i64>>(),
+            *mode,
+        ),
+        Action::ExplodeFudge(values) => explode_pools(
+            pools,
+            dice_to_reroll(action, group)?,
+            &values
+                .iter()
+                .map(|value| value.value())
+                .collect::<Vec<i64>>(),
+        ),
+        Action::Negate => apply_numeric(pools, action, group),
+        _ => Err(Error::incompatible(&action.to_string(), "fudge roll")),
+    }
+}
+
+fn dice_to_reroll<'a, 'b>(
+    action: &Action,
+    group: Option<&'a Group<'b>>,
+) -> Result<&'a Group<'b>, Error> {
+    group.ok_or_else(|| Error::not_computable(&format!("{} on a total", action)))
+}
+
+fn keep_pools<F: Fn(&[i64], usize) -> Vec<i64>>(
+    pools: Pools,
+    keep: DiceNumber,
+    f: F,
+) -> Result<Pools, Error> {
+    if let Some(pool) = pools.keys().find(|pool| keep as usize > pool.len()) {
+        return Err(Error::bad_action_parameter(&format!(
+            "Can't keep {} rolls because there are only {} available rolls.",
+            keep,
+            pool.len()
+        )));
+    }
+    Ok(map_pools(pools, |pool| f(pool, keep as usize)))
+}
+
+/// Keep the set of rolls with the best (or worst) sum out of two independent rolls of the group.
+fn roll_twice_pools(pools: &Pools, best: bool) -> Pools {
+    let sums = pools_sum(pools);
+    pools
+        .iter()
+        .map(|(pool, p)| {
+            let sum: i64 = pool.iter().sum();
+            // The pool is kept when it is the first one and the other one is not better,
+            // or when it is the second one and the first one is worse
+            let (not_better, worse) = if best {
+                (
+                    sums.probabilities.range(..=sum),
+                    sums.probabilities.range(..sum),
+                )
+            } else {
+                (
+                    sums.probabilities.range(sum..),
+                    sums.probabilities.range(sum + 1..),
+                )
+            };
+            let kept: Probability = not_better.map(|(_, q)| q).sum::<Probability>()
+                + worse.map(|(_, q)| q).sum::<Probability>();
+            (pool.clone(), p * kept)
+        })
+        .collect()
+}
+
+/// The faces of the dice matching the condition of an action.
+fn matching_faces(dice: &Distribution, condition: &RollCondition) -> Vec<i64> {
+    dice.probabilities
+        .keys()
+        .filter(|value| condition.matches(**value as NumericRoll))
+        .cloned()
+        .collect()
+}
+
+fn reroll_pools(
+    pools: Pools,
+    dice: &Distribution,
+    values: &[i64],
+    mode: RerollMode,
+) -> Result<Pools, Error> {
+    // Rerolling until the roll is none of the values is rolling the other faces only
+    let rerolled_until;
+    let dice = match mode {
+        RerollMode::Once => dice,
+        RerollMode::Until => {
+            let remaining = 1.0 - dice.probability_of_any(values);
+            if remaining <= 0.0 {
+                return Err(Error::not_computable(&format!(
+                    "an endless reroll on {:?}",
+                    values
+                )));
+            }
+            rerolled_until = Distribution {
+                probabilities: dice
+                    .probabilities
+                    .iter()
+                    .filter(|(value, _)| !values.contains(value))
+                    .map(|(value, p)| (*value, p / remaining))
+                    .collect(),
+            };
+            &rerolled_until
+        }
+    };
+    let mut new_pools: Pools = HashMap::new();
+    for (pool, p) in pools.into_iter() {
+        let kept: Vec<i64> = pool
+            .iter()
+            .filter(|roll| !values.contains(roll))
+            .cloned()
+            .collect();
+        for (rerolls, q) in roll_pools(pool.len() - kept.len(), dice)?.iter() {
+            *new_pools
+                .entry(insert_sorted(&kept, rerolls))
+                .or_insert(0.0) += p * q;
+        }
+        check_pools_size(&new_pools)?;
+    }
+    Ok(new_pools)
+}
+
+fn explode_pools(pools: Pools, group: &Group, values: &[i64]) -> Result<Pools, Error> {
+    if group.dice.probability_of_any(values) >= 1.0 {
+        return Err(Error::not_computable(&format!(
+            "an endless explosion on {:?}",
+            values
+        )));
+    }
+    // Each exploding die spawns its own chain of new rolls, independently of the others
+    let join = |pool: &[i64], new_rolls: &[i64]| {
+        if group.sum_only {
+            vec![pool.iter().chain(new_rolls).sum()]
+        } else {
+            insert_sorted(pool, new_rolls)
+        }
+    };
+    let mut by_exploding: Vec<Pools> = vec![];
+    for (pool, p) in pools.into_iter() {
+        let exploding = pool.iter().filter(|roll| values.contains(roll)).count();
+        if by_exploding.len() <= exploding {
+            by_exploding.resize(exploding + 1, HashMap::new());
+        }
+        *by_exploding[exploding]
+            .entry(join(&pool, &[]))
+            .or_insert(0.0) += p;
+    }
+    let chain = explosion_chain(group, values)?;
+    let mut chains: Pools = HashMap::new();
+    chains.insert(vec![], 1.0);
+    let mut new_pools: Pools = HashMap::new();
+    for (exploding, pools) in by_exploding.iter().enumerate() {
+        if exploding > 0 {
+            chains = join_pools(&chains, &chain, join)?;
+        }
+        for (pool, p) in join_pools(pools, &chains, join)?.into_iter() {
+            *new_pools.entry(pool).or_insert(0.0) += p;
+        }
+        check_pools_size(&new_pools)?;
+    }
+    Ok(new_pools)
+}
+
+/// Compute the sets of new rolls spawned by one exploding die.
+///
+/// The explosions are followed up to the maximum depth: the longer chains
+/// would make the roll fail, so they are left out.
+fn explosion_chain(group: &Group, values: &[i64]) -> Result<Pools, Error> {
+    let mut chain: Pools = HashMap::new();
+    let mut exploding: Pools = HashMap::new();
+    exploding.insert(vec![], 1.0);
+    for _ in 0..group.max_explosion_depth {
+        let mut next_exploding: Pools = HashMap::new();
+        for (rolls, p) in exploding.iter() {
+            for (value, q) in group.dice.probabilities.iter() {
+                let new_rolls = if group.sum_only {
+                    vec![rolls.iter().sum::<i64>() + value]
+                } else {
+                    insert_sorted(rolls, &[*value])
+                };
+                let pools = if values.contains(value) {
+                    &mut next_exploding
+                } else {
+                    &mut chain
+                };
+                *pools.entry(new_rolls).or_insert(0.0) += p * q;
+            }
+            check_pools_size(&next_exploding)?;
+            check_pools_size(&chain)?;
+        }
+        exploding = next_exploding;
+    }
+    Ok(chain)
+}
+
+/// Combine every set of rolls of `pools` with every set of rolls of `other_pools`.
+fn join_pools<F: Fn(&[i64], &[i64]) -> Vec<i64>>(
+    pools: &Pools,
+    other_pools: &Pools,
+    join: F,
+) -> Result<Pools, Error> {
+    if pools.len().saturating_mul(other_pools.len()) > MAX_COMBINATIONS {
+        return Err(Error::not_computable(&String::from(
+            "too many dice combinations",
+        )));
+    }
+    let mut new_pools: Pools = HashMap::new();
+    for (pool, p) in pools.iter() {
+        for (other_pool, q) in other_pools.iter() {
+            *new_pools.entry(join(pool, other_pool)).or_insert(0.0) += p * q;
+        }
+    }
+    check_pools_size(&new_pools)?;
+    Ok(new_pools)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::ErrorKind;
+    use crate::io::read::read_request;
+    use crate::probability::*;
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!(
+            (value - expected).abs() < 1e-9,
+            "{} is not close to {}",
+            value,
+            expected
+        );
+    }
+
+    fn distribution_of(request: &str) -> Result<Distribution, Error> {
+        read_request(request).unwrap().distribution()
+    }
+
+    #[test]
+    fn numbered_dice_distribution() {
+        let distribution = distribution_of("2D6 +3").unwrap();
+        assert_eq!(distribution.min(), 5);
+        assert_eq!(distribution.max(), 15);
+        assert_close(distribution.mean(), 10.0);
+        assert_close(distribution.variance(), 35.0 / 6.0);
+        assert_close(distribution.probability(10), 6.0 / 36.0);
+        assert_close(distribution.probability_at_least(14), 3.0 / 36.0);
+        assert_close(distribution.probability_at_least(5), 1.0);
+        assert_close(distribution.probability_at_least(16), 0.0);
+    }
+
+    #[test]
+    fn keep_distribution() {
+        let distribution = distribution_of("2D20 KeepBest(1)").unwrap();
+        assert_close(distribution.probability(20), 39.0 / 400.0);
+        assert_close(distribution.probability(1), 1.0 / 400.0);
+
+        let distribution = distribution_of("(2D20 KeepWorst(1))").unwrap();
+        assert_close(distribution.probability(1), 39.0 / 400.0);
+        assert_close(distribution.probability(20), 1.0 / 400.0);
+
+        assert_eq!(
+            *distribution_of("2D20 KeepBest(3)").unwrap_err().kind(),
+            ErrorKind::BadActionParameter(String::from(
+                "Can't keep 3 rolls because there are only 2 available rolls."
+            ))
+        );
+    }
+
+    #[test]
+    fn skill_check_distribution() {
+        let distribution = distribution_of("1D% Skill(50)").unwrap();
+        assert_close(distribution.probability(3), 0.10);
+        assert_close(distribution.probability(2), 0.15);
+        assert_close(distribution.probability(1), 0.25);
+        assert_close(distribution.probability(0), 0.50);
+        assert!(distribution_of("1D% Bonus").is_err());
+        // The levels are not numbers to add up
+        assert!(distribution_of("2D% Skill(50) Total").is_err());
+
+        // The percentile dice is still a uniform roll from 1 to 100
+        assert_eq!(
+            distribution_of("d%").unwrap(),
+            distribution_of("1D100").unwrap()
+        );
+    }
+
+    #[test]
+    fn multiply_and_total_distribution() {
+        let distribution = distribution_of("1D4 +1 Total x2").unwrap();
+        assert_eq!(
+            distribution.probabilities().keys().collect::<Vec<&i64>>(),
+            vec![&4, &6, &8, &10]
+        );
+        let distribution = distribution_of("(1D6 x3)").unwrap();
+        assert_eq!(distribution.min(), 3);
+        assert_eq!(distribution.max(), 18);
+    }
+
+    #[test]
+    fn reroll_and_explode_distribution() {
+        // Reroll once: a 1 can still come back
+        let distribution = distribution_of("(1D6 Reroll(1))").unwrap();
+        assert_close(distribution.probability(1), 1.0 / 36.0);
+        assert_close(distribution.probability(6), 7.0 / 36.0);
+        // Reroll until: a 1 or a 2 never comes back
+        let distribution = distribution_of("(1D6 RerollUntil(<3))").unwrap();
+        assert_close(distribution.probability(2), 0.0);
+        assert_close(distribution.probability(6), 1.0 / 4.0);
+        assert_eq!(
+            *distribution_of("(+6 RerollUntil(6))").unwrap_err().kind(),
+            ErrorKind::NotComputable(String::from(
+                "The probability distribution of an endless reroll on [6] can't be computed exactly"
+            ))
+        );
+
+        let distribution = distribution_of("(1D6 Explode(6))").unwrap();
+        assert_close(distribution.probability(7), 1.0 / 36.0);
+        assert_close(distribution.probability(6), 0.0);
+        // Explosions are only followed up to the explosion depth limit
+        assert!((distribution.mean() - 4.2).abs() < 1e-6);
+        let threshold = distribution_of("(1D10 Explode(>=9))").unwrap();
+        let values = distribution_of("(1D10 Explode(9,10))").unwrap();
+        for (value, p) in values.probabilities() {
+            assert_close(threshold.probability(*value), *p);
+        }
+        assert!(distribution_of("1d6!!").is_err());
+
+        assert_eq!(
+            *distribution_of("(+6 Explode(6))").unwrap_err().kind(),
+            ErrorKind::NotComputable(String::from(
+                "The probability distribution of an endless explosion on [6] can't be computed exactly"
+            ))
+        );
+    }
+
+    #[test]
+    fn long_explosions_distribution() {
+        let distribution = distribution_of("6D6 Explode(6)").unwrap();
+        assert!((distribution.mean() - 25.2).abs() < 1e-6);
+        let distribution = distribution_of("(3D6 Explode(6) KeepBest(2))").unwrap();
+        assert_close(distribution.probability(2), 1.0 / 216.0);
+
+        // The second explosion also explodes the sixes spawned by the first one
+        let distribution = distribution_of("(1D6 Explode(6) Explode(6))").unwrap();
+        assert!((distribution.mean() - 4.2 * 1.2).abs() < 1e-6);
+
+        // The rolls exploding too many times are left out
+        let limits = Limits {
+            max_explosion_depth: 2,
+            ..Limits::default()
+        };
+        let distribution = read_request("(1D6 Explode(6))")
+            .unwrap()
+            .distribution_with_limits(&limits)
+            .unwrap();
+        assert_close(distribution.probability(17), 1.0 / 216.0);
+        assert_close(distribution.probability_at_least(18), 0.0);
+        assert_close(distribution.probability_at_least(1), 215.0 / 216.0);
+
+        assert_eq!(
+            *distribution_of("(20D100 Explode(>=10))").unwrap_err().kind(),
+            ErrorKind::NotComputable(String::from(
+                "The probability distribution of too many dice combinations can't be computed exactly"
+            ))
+        );
+    }
+
+    #[test]
+    fn advantage_distribution() {
+        let distribution = distribution_of("1d20adv +5").unwrap();
+        assert_close(distribution.probability(25), 39.0 / 400.0);
+        assert_close(distribution.probability(6), 1.0 / 400.0);
+        let keep_best = distribution_of("(2D20 KeepBest(1)) +5").unwrap();
+        for (value, p) in keep_best.probabilities() {
+            assert_close(distribution.probability(*value), *p);
+        }
+
+        // The whole group is rolled twice, not each die
+        let distribution = distribution_of("(2D6 Disadvantage)").unwrap();
+        assert_close(distribution.probability(12), 1.0 / 1296.0);
+        assert_close(distribution.probability(2), 71.0 / 1296.0);
+        assert!(distribution_of("3D6 Sum Advantage").is_err());
+    }
+
+    #[test]
+    fn successes_distribution() {
+        let distribution = distribution_of("2D10 Successes(>=8) Botch(1)").unwrap();
+        assert_close(distribution.probability(2), 9.0 / 100.0);
+        assert_close(distribution.probability(1), 2.0 * 3.0 * 6.0 / 100.0);
+        assert_close(distribution.probability(-2), 1.0 / 100.0);
+        assert_close(distribution.probability(0), 2.0 * 0.3 * 0.1 + 0.36);
+    }
+
+    #[test]
+    fn negative_distribution() {
+        let distribution = distribution_of("1D20 -2").unwrap();
+        assert_eq!(distribution.min(), -1);
+        assert_eq!(distribution.max(), 18);
+
+        let distribution = distribution_of("2D6 - 1D4").unwrap();
+        assert_eq!(distribution.min(), -2);
+        assert_eq!(distribution.max(), 11);
+        assert_close(distribution.mean(), 4.5);
+
+        let distribution = distribution_of("1D6 -(2D6 KeepBest(1)) KeepBest(1)").unwrap();
+        assert_eq!(distribution.max(), 5);
+    }
+
+    #[test]
+    fn expression_distribution() {
+        let distribution = distribution_of("(1D6 + 1) x 2").unwrap();
+        assert_eq!(distribution.min(), 4);
+        assert_eq!(distribution.max(), 14);
+        assert_close(distribution.mean(), 9.0);
+        assert_close(distribution.probability(5), 0.0);
+
+        let distribution = distribution_of("= 1D6 / 2 up + 1D6 / 2 down").unwrap();
+        assert_close(distribution.probability(1), 1.0 / 3.0 * 1.0 / 6.0);
+        assert_close(distribution.probability(6), 1.0 / 3.0 * 1.0 / 6.0);
+
+        assert!(distribution_of("2D6 / (1D2 - 1) up").is_err());
+    }
+
+    #[test]
+    fn custom_dice_distribution() {
+        let distribution = distribution_of("1D{2,3,3,4,4,5}").unwrap();
+        assert_close(distribution.mean(), 3.5);
+        assert_close(distribution.probability(3), 1.0 / 3.0);
+        let distribution = distribution_of("2D{0,0,1}").unwrap();
+        assert_close(distribution.probability(0), 4.0 / 9.0);
+        assert_close(distribution.probability(2), 1.0 / 9.0);
+    }
+
+    #[test]
+    fn fudge_distribution() {
+        let distribution = distribution_of("4F").unwrap();
+        assert_eq!(distribution.min(), -4);
+        assert_eq!(distribution.max(), 4);
+        assert_close(distribution.mean(), 0.0);
+        assert_close(distribution.probability(4), 1.0 / 81.0);
+
+        // Totaled with numeric rolls, or in an expression, fudge rolls count for their value
+        let distribution = distribution_of("4F +2 Total Successes(>=5)").unwrap();
+        assert_close(distribution.probability(1), 5.0 / 81.0);
+        let distribution = distribution_of("(1F + 1) x 2").unwrap();
+        assert_eq!(
+            distribution.probabilities.keys().collect::<Vec<_>>(),
+            vec![&0, &2, &4]
+        );
+        let distribution = distribution_of("1D6 - 4F").unwrap();
+        assert_eq!(distribution.min(), -3);
+        assert_eq!(distribution.max(), 10);
+    }
+
+    #[test]
+    fn not_computable_distribution() {
+        for request in ["1D20 Flip", "3D6 Concat", "3D6 RerollBest(1)", "5D6 Count"].iter() {
+            match distribution_of(request).unwrap_err().kind() {
+                ErrorKind::NotComputable(_) => (),
+                kind => panic!("Unexpected error {:?} for {}", kind, request),
+            }
+        }
+    }
+}
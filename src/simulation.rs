@@ -0,0 +1,261 @@
+//! `simulation` rolls a request many times and collects statistics on the results.
+//!
+//! Unlike [probability](../probability/index.html), it works for any request, including
+//! the actions that can't be computed exactly (like [FlipFlop](../actions/trait.FlipFlop.html)).
+
+use crate::actions::Aggregation;
+use crate::dice::*;
+use crate::errors::Error;
+use crate::MultiTypeRequest;
+use std::collections::BTreeMap;
+
+/// Number of occurences of each value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct Histogram {
+    counts: BTreeMap<i64, u64>,
+}
+
+impl Histogram {
+    pub fn new() -> Histogram {
+        Histogram::default()
+    }
+
+    pub fn add(&mut self, value: i64) {
+        self.add_occurences(value, 1);
+    }
+
+    pub fn add_occurences(&mut self, value: i64, occurences: u64) {
+        *self.counts.entry(value).or_insert(0) += occurences;
+    }
+
+    /// Number of occurences of each value, sorted by value.
+    pub fn counts(&self) -> &BTreeMap<i64, u64> {
+        &self.counts
+    }
+
+    /// Total number of occurences.
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.counts.keys().next().cloned()
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.counts.keys().next_back().cloned()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        match self.total() {
+            0 => None,
+            total => Some(
+                self.counts
+                    .iter()
+                    .map(|(value, count)| *value as f64 * *count as f64)
+                    .sum::<f64>()
+                    / total as f64,
+            ),
+        }
+    }
+
+    /// Smallest value such that at least `percent`% of the occurences are lower or equal to it.
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::simulation::Histogram;
+    /// let mut histogram = Histogram::new();
+    /// for value in 1..=10 {
+    ///     histogram.add(value);
+    /// }
+    /// assert_eq!(histogram.percentile(50.0), Some(5));
+    /// assert_eq!(histogram.percentile(90.0), Some(9));
+    /// assert_eq!(histogram.percentile(100.0), Some(10));
+    /// ```
+    pub fn percentile(&self, percent: f64) -> Option<i64> {
+        let rank = (percent / 100.0 * self.total() as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (value, count) in self.counts.iter() {
+            seen += count;
+            if seen >= rank {
+                return Some(*value);
+            }
+        }
+        None
+    }
+}
+
+/// Results of many rolls of the same request.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct Simulation {
+    pub(crate) runs: u32,
+    pub(crate) rolls: Histogram,
+    pub(crate) counts: BTreeMap<String, Histogram>,
+}
+
+impl Simulation {
+    /// Number of times the request was rolled.
+    pub fn runs(&self) -> u32 {
+        self.runs
+    }
+
+    /// Histogram of all the final rolls values (fudge rolls count as +1, 0 or -1).
+    pub fn rolls(&self) -> &Histogram {
+        &self.rolls
+    }
+
+    /// Histogram of each [CountValues](../actions/trait.CountValues.html) bucket, by bucket description.
+    pub fn counts(&self) -> &BTreeMap<String, Histogram> {
+        &self.counts
+    }
+}
+
+impl MultiTypeRequest {
+    /// Roll the request `runs` times and collect the histograms of the results.
+    ///
+    /// # Arguments
+    /// * `runs` Number of times the request is rolled
+    /// * `default_total` If set to `true`, in the absence of a parsed aggregation, the `ToTal` action will be applied to numeric rolls.
+    /// * `dice` Dice generator used for all the rolls
+    ///
+    /// # Example
+    /// ```
+    /// # use letsroll::io::read::read_request;
+    /// # use letsroll::dice::DiceGenerator;
+    /// let request = read_request("1D20 Flip").unwrap();
+    /// let simulation = request.simulate(1000, true, DiceGenerator::from_seed(42)).unwrap();
+    /// assert_eq!(simulation.rolls().total(), 1000);
+    /// assert_eq!(simulation.rolls().max(), Some(91));
+    /// ```
+    pub fn simulate(
+        &self,
+        runs: u32,
+        default_total: bool,
+        dice: DiceGenerator,
+    ) -> Result<Simulation, Error> {
+        let mut rolls = Histogram::new();
+        let mut counts: BTreeMap<String, Histogram> = BTreeMap::new();
+        for _ in 0..runs {
+            let session = self.roll(default_total, dice.clone())?;
+            if let Some(numeric_session) = &session.numeric_session {
+                for numeric_rolls in numeric_session.rolls.iter() {
+                    match self.aggregation {
+                        Some(Aggregation::CountValues) => counts
+                            .entry(numeric_rolls.description.clone())
+                            .or_default()
+                            .add(i64::from(numeric_rolls.rolls[0])),
+                        _ => {
+                            for roll in numeric_rolls.rolls.iter() {
+                                rolls.add(i64::from(*roll));
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(fudge_session) = &session.fudge_session {
+                for fudge_rolls in fudge_session.rolls.iter() {
+                    for roll in fudge_rolls.rolls.iter() {
+                        rolls.add(roll.value());
+                    }
+                }
+            }
+            // The symbols are only counted, once aggregated
+            if let Some(symbol_results) = &session.symbol_results {
+                for symbol_rolls in symbol_results.rolls.iter() {
+                    counts
+                        .entry(symbol_rolls.description.clone())
+                        .or_default()
+                        .add(i64::from(symbol_rolls.rolls[0]));
+                }
+            }
+        }
+        // A value that is not rolled during a run is not counted at all
+        for histogram in counts.values_mut() {
+            let missing = u64::from(runs) - histogram.total();
+            if missing > 0 {
+                histogram.add_occurences(0, missing);
+            }
+        }
+        Ok(Simulation {
+            runs,
+            rolls,
+            counts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dice::DiceGenerator;
+    use crate::io::read::read_request;
+    use crate::simulation::*;
+
+    #[test]
+    fn histogram_statistics() {
+        let mut histogram = Histogram::new();
+        assert_eq!(histogram.mean(), None);
+        assert_eq!(histogram.percentile(50.0), None);
+
+        histogram.add(2);
+        histogram.add_occurences(4, 3);
+        assert_eq!(histogram.total(), 4);
+        assert_eq!(histogram.min(), Some(2));
+        assert_eq!(histogram.max(), Some(4));
+        assert_eq!(histogram.mean(), Some(3.5));
+        assert_eq!(histogram.percentile(0.0), Some(2));
+        assert_eq!(histogram.percentile(25.0), Some(2));
+        assert_eq!(histogram.percentile(26.0), Some(4));
+    }
+
+    #[test]
+    fn simulate_total() {
+        let request = read_request("3D6 +2").unwrap();
+        let simulation = request
+            .simulate(2000, true, DiceGenerator::from_seed(3))
+            .unwrap();
+        assert_eq!(simulation.runs(), 2000);
+        assert_eq!(simulation.rolls().total(), 2000);
+        assert!(simulation.rolls().min().unwrap() >= 5);
+        assert!(simulation.rolls().max().unwrap() <= 20);
+        assert!((simulation.rolls().mean().unwrap() - 12.5).abs() < 0.5);
+        assert!(simulation.counts().is_empty());
+    }
+
+    #[test]
+    fn simulate_rolls_without_total() {
+        let request = read_request("3D6 4F").unwrap();
+        let simulation = request
+            .simulate(100, false, DiceGenerator::from_seed(3))
+            .unwrap();
+        assert_eq!(simulation.rolls().total(), 700);
+        assert_eq!(simulation.rolls().min(), Some(-1));
+        assert_eq!(simulation.rolls().max(), Some(6));
+    }
+
+    #[test]
+    fn simulate_count_values() {
+        let request = read_request("4F Count").unwrap();
+        let simulation = request
+            .simulate(500, true, DiceGenerator::from_seed(3))
+            .unwrap();
+        assert_eq!(
+            simulation.counts().keys().collect::<Vec<&String>>(),
+            vec!["COUNT(+)", "COUNT(-)", "COUNT(0)"]
+        );
+        for histogram in simulation.counts().values() {
+            assert_eq!(histogram.total(), 500);
+            assert!(histogram.max().unwrap() <= 4);
+        }
+        assert!(simulation.rolls().counts().is_empty());
+    }
+
+    #[test]
+    fn simulate_error() {
+        let request = read_request("2D6 KeepBest(3)").unwrap();
+        assert!(request
+            .simulate(10, true, DiceGenerator::from_seed(3))
+            .is_err());
+    }
+}
@@ -0,0 +1,63 @@
+use std::process::{Command, Output};
+
+fn letsroll(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_letsroll"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn roll_file() {
+    let output = letsroll(&["-f", "examples/rolls/sheet.roll", "--seed", "1"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+    for label in &["attack", "damage", "save", "initiative"] {
+        assert!(
+            stdout.contains(&format!("Rolling {}...", label)),
+            "{}",
+            stdout
+        );
+    }
+
+    // Only the requests with the label
+    let output = letsroll(&[
+        "-f",
+        "examples/rolls/sheet.roll",
+        "damage",
+        "--max-dice",
+        "10",
+    ]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Rolling damage...\n"), "{}", stdout);
+    assert!(!stdout.contains("attack"), "{}", stdout);
+}
+
+#[test]
+fn roll_fudge_with_modifier() {
+    // Without Total, the fudge dice are shown apart from the sum of the modifiers
+    let output = letsroll(&["4F +2", "--seed", "5"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.contains("\nNUMERIC SUM \t: 2\n"), "{}", stdout);
+    assert!(stdout.contains("\n4F: "), "{}", stdout);
+    assert!(!stdout.contains("TOTAL SUM"), "{}", stdout);
+
+    // With Total, they make a single number on the Fate ladder
+    let output = letsroll(&["4F +2 Total", "--seed", "5"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\nTOTAL SUM \t: "), "{}", stdout);
+    assert!(stdout.contains("\nFATE LADDER \t: "), "{}", stdout);
+}
+
+#[test]
+fn roll_with_limits() {
+    let output = letsroll(&["1D6 Explode(1..6)", "--max-explosions", "3"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "FAILURE : Limit exceeded error: More than 3 successive explosions\n"
+    );
+}